@@ -0,0 +1,56 @@
+// Times move generation on the bench positions, and perft with bulk counting, which is dominated
+// by it. Run with "cargo bench --bench movegen"
+use chess_v3::bench::BENCH_POSITIONS;
+use chess_v3::board::Board;
+use chess_v3::consts::EMPTY_BOARD;
+use chess_v3::perft;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+const ROUNDS: usize = 20_000;
+//                                Position, Depth
+const PERFT_POSITIONS: [(&str, i32); 2] = [
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 5),
+    ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 4),
+];
+
+fn load(fen: &str) -> Board {
+    let mut board = EMPTY_BOARD;
+    board.load_fen(fen.to_string());
+    board
+}
+
+fn report(name: &str, count: usize, unit: &str, start: Instant) {
+    let time = start.elapsed().as_secs_f64();
+    println!("{:<22}: {:>10} {} in {:>6.0} ms, {:>10.0} {}/second", name, count, unit, time * 1000.0, count as f64 / time, unit);
+}
+
+fn main() {
+    let mut boards = BENCH_POSITIONS.iter().map(|fen| load(fen)).collect::<Vec<Board>>();
+
+    let start = Instant::now();
+    let mut count = 0;
+    for _ in 0..ROUNDS {
+        for board in &boards {
+            count += black_box(board).generate_moves().len();
+        }
+    }
+    report("Pseudo-legal moves", count, "moves", start);
+
+    let start = Instant::now();
+    let mut count = 0;
+    for _ in 0..ROUNDS / 10 {
+        for board in &mut boards {
+            count += black_box(&mut *board).generate_legal_moves().len();
+        }
+    }
+    report("Legal moves", count, "moves", start);
+
+    for (fen, depth) in PERFT_POSITIONS {
+        let mut board = load(fen);
+        let start = Instant::now();
+        let count = perft::perft_nodes(depth, &mut board, None);
+        report(&format!("Perft {depth} {}", &fen[..8]), count as usize, "nodes", start);
+    }
+}
@@ -0,0 +1,52 @@
+// Stands in for a reference engine when testing perftdiff. It passes every command on to the chess_v3
+// of the same build, but acts as if its move generator missed the first move of the first line: that move is left
+// out of a divide at depth 1, and the first move of a deeper divide counts one node less
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+fn main() {
+    // Examples are built into an examples directory next to the binaries
+    let examples = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let path = examples.with_file_name(format!("chess_v3{}", std::env::consts::EXE_SUFFIX));
+    let mut engine = Command::new(path).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    let mut input = engine.stdin.take().unwrap();
+    let mut output = BufReader::new(engine.stdout.take().unwrap()).lines();
+
+    for line in std::io::stdin().lines() {
+        let line = line.unwrap();
+        writeln!(input, "{line}").unwrap();
+        input.flush().unwrap();
+
+        let (end, depth) = match line.trim() {
+            "quit" => break,
+            "uci" => ("uciok", 0),
+            "isready" => ("readyok", 0),
+            command => match command.strip_prefix("go perft ").and_then(|depth| depth.trim().parse::<u32>().ok()) {
+                Some(depth) => ("Nodes searched", depth),
+                None => continue,
+            },
+        };
+
+        let mut first_move = true;
+        let mut missed = 0;
+        for line in output.by_ref() {
+            let line = line.unwrap();
+            if let Some(nodes) = line.strip_prefix("Nodes searched: ") {
+                println!("Nodes searched: {}", nodes.trim().parse::<u64>().unwrap() - missed);
+            } else if let Some((mov, count)) = line.split_once(": ").filter(|_| first_move && depth > 0) {
+                first_move = false;
+                let count = count.trim().parse::<u64>().unwrap();
+                missed = if depth == 1 { count } else { 1 };
+                if depth > 1 {
+                    println!("{mov}: {}", count - 1);
+                }
+            } else {
+                println!("{line}");
+            }
+            if line.starts_with(end) {
+                break;
+            }
+        }
+    }
+    engine.wait().unwrap();
+}
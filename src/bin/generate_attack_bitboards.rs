@@ -1,3 +1,6 @@
+// The tables are indexed by square, so the loops are too
+#![allow(clippy::needless_range_loop)]
+
 fn file_diff(pos1: i32, pos2: i32) -> i32 {
     i32::abs(pos2 % 8 - pos1 % 8)
 }
@@ -12,7 +15,7 @@ fn generate_king_attacks() -> [u64; 64] {
     for square in 0..64 {
         for offset in offsets {
             let pos = square as i32 + offset;
-            if pos >= 0 && pos <= 63 
+            if (0..=63).contains(&pos)
                 && (i32::abs(square as i32 % 8 - pos % 8) <= 1
                     && i32::abs(square as i32 / 8 - pos / 8) <= 1) {
                 
@@ -63,7 +66,7 @@ fn generate_black_pawn_attacks() -> [u64; 64] {
     for square in 8..56 {
         for offset in offsets {
             let pos: i32 = square as i32 + offset;
-            if pos >= 0 && i32::abs((square % 8) as i32 - (pos % 8) as i32) == 1 {
+            if pos >= 0 && i32::abs((square % 8) as i32 - pos % 8) == 1 {
                 bitboards[square] |= 1 << pos;
             }
         }
@@ -184,28 +187,30 @@ fn generate_south_west_rays() -> [u64; 64] {
     bitboards
 }
 
-fn get_squares(bitboard: u64) -> Vec<usize> {
-    let mut result = Vec::new();
-    for square in 0..64 {
-        if (1 << square) & bitboard > 0 {
-            result.push(square);
-        }
-    }
-    result
-}
+type Generator = fn() -> [u64; 64];
 
+// Usage: generate_attack_bitboards <table>
+// Prints the table as an array, to be pasted into attack_bitboards.rs
 fn main() {
-    let white_pawn_pushes = generate_white_pawn_pushes();
-    let black_pawn_pushes = generate_black_pawn_pushes();
-    // let rays: [u64; 64] = generate_east_rays();
-    // let rays: [u64; 64] = generate_north_rays();
-    // let rays: [u64; 64] = generate_west_rays();
-    // let rays: [u64; 64] = generate_south_rays();
-    // let rays: [u64; 64] = generate_north_east_rays();
-    // let rays: [u64; 64] = generate_north_west_rays();
-    // let rays: [u64; 64] = generate_south_east_rays();
-    // let rays: [u64; 64] = generate_south_west_rays();
-    println!("{:?}", white_pawn_pushes);
-    println!("{:?}", black_pawn_pushes);
+    let tables: [(&str, Generator); 13] = [
+        ("king_attacks", generate_king_attacks),
+        ("white_pawn_pushes", generate_white_pawn_pushes),
+        ("black_pawn_pushes", generate_black_pawn_pushes),
+        ("white_pawn_attacks", generate_white_pawn_attacks),
+        ("black_pawn_attacks", generate_black_pawn_attacks),
+        ("east_rays", generate_east_rays),
+        ("north_rays", generate_north_rays),
+        ("west_rays", generate_west_rays),
+        ("south_rays", generate_south_rays),
+        ("north_east_rays", generate_north_east_rays),
+        ("north_west_rays", generate_north_west_rays),
+        ("south_east_rays", generate_south_east_rays),
+        ("south_west_rays", generate_south_west_rays),
+    ];
+
+    let name = std::env::args().nth(1).unwrap_or_default();
+    match tables.iter().find(|(table, _)| *table == name) {
+        Some((_, generate)) => println!("{:?}", generate()),
+        None => println!("Usage: generate_attack_bitboards <{}>", tables.map(|(table, _)| table).join("|")),
+    }
 }
-
@@ -1,50 +1,100 @@
+//! The position: pieces, side to move, castling and en passant state, and making and unmaking moves.
 use crate::types::*;
 use crate::consts::*;
+#[cfg(feature = "nnue")]
+use crate::nnue::AccumulatorStack;
 
+/// A position, with the history needed to unmake moves. Squares are numbered from a1 = 0 to h8 = 63.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Board {
-    pub white_pieces: u64,
-    pub black_pieces: u64,
-    pub pawns: u64,
-    pub rooks: u64,
-    pub knights: u64,
-    pub bishops: u64,
-    pub queens: u64,
-    pub kings: u64,
-
+    /// The squares of the white pieces.
+    pub white_pieces: Bitboard,
+    /// The squares of the black pieces.
+    pub black_pieces: Bitboard,
+    /// The squares of the pawns of both colors.
+    pub pawns: Bitboard,
+    /// The squares of the rooks of both colors.
+    pub rooks: Bitboard,
+    /// The squares of the knights of both colors.
+    pub knights: Bitboard,
+    /// The squares of the bishops of both colors.
+    pub bishops: Bitboard,
+    /// The squares of the queens of both colors.
+    pub queens: Bitboard,
+    /// The squares of the kings of both colors.
+    pub kings: Bitboard,
+
+    /// The side to move.
     pub turn: Color,
+    /// Which castling moves are still allowed, regardless of what stands in the way.
     pub castling_rights: Castling,
-    pub en_passant: Square,
+    /// The square a pawn passed with a double push on the last move, if any.
+    pub en_passant: Option<Square>,
+    /// Halfmoves since the last capture or pawn move, for the fifty-move rule.
     pub halfmove_clock: Clock,
+    /// The number of the move, starting at 1 and increased after each Black move.
     pub fullmove_clock: Clock,
 
-    //              EP      Castling  HM     Captured   EP Capture
-    pub moves: Vec<(Square, Castling, Clock, Piece,     (Square, Piece))>,
+    /// One entry per move made, to unmake it again.
+    pub moves: Vec<MoveHistory>,
+
+    /// The NNUE accumulators of each position in the move history, when NNUE is enabled.
+    #[cfg(feature = "nnue")]
+    pub accumulators: AccumulatorStack,
 }
 
 impl Board {
-    pub fn square_to_string(square: Square) -> String {
-        (('a' as Square + (square % 8)) as u8 as char).to_string()
-            + &(('1' as Square + (square / 8)) as u8 as char).to_string()
+    /// Long algebraic notation as used by UCI, e.g. e2e4 or e7e8q. The flags of the move come from
+    /// this position, the move does not have to be legal in it
+    pub fn string_to_move(&self, string: &str) -> Option<Move> {
+        if !(4..=5).contains(&string.len()) || !string.is_char_boundary(2) || !string.is_char_boundary(4) {
+            return None;
+        }
+        let start_square = string[0..2].parse::<Square>().ok()?;
+        let end_square = string[2..4].parse::<Square>().ok()?;
+
+        let promotion = match string.as_bytes().get(4) {
+            None       => PieceType::Empty,
+            Some(b'r') => PieceType::Rook,
+            Some(b'n') => PieceType::Knight,
+            Some(b'b') => PieceType::Bishop,
+            Some(b'q') => PieceType::Queen,
+            Some(_)    => return None,
+        };
+        Some(self.new_move(start_square, end_square, promotion))
     }
 
-    pub fn string_to_square(string: String) -> Square {
-        (string.chars().nth(0).unwrap() as Square - 'a' as Square)
-            + (string.chars().nth(1).unwrap() as Square - '1' as Square) * 8
+    /// The move of the piece on `start_square` to `end_square`, with its flags set from this position
+    pub fn new_move(&self, start_square: Square, end_square: Square, promotion: PieceType) -> Move {
+        let piece = self.get_piece(start_square);
+        let capture = if self.get_piece(end_square) != EMPTY_PIECE { Move::CAPTURE } else { Move::QUIET };
+
+        let flags = if promotion != PieceType::Empty {
+            capture | Move::promotion_flags(promotion)
+        } else if piece.typ == PieceType::Pawn && self.en_passant == Some(end_square) {
+            Move::EN_PASSANT
+        } else if piece.typ == PieceType::King && (start_square.file() as i32 - end_square.file() as i32).abs() == 2 {
+            Move::CASTLE
+        } else {
+            capture
+        };
+        Move::new(start_square, end_square, flags)
     }
 
+    /// Prints the board as a grid of piece letters, White in upper case, with the eighth rank on top.
     pub fn print_board(&self) {
         println!(" --- --- --- --- --- --- --- ---");
         for i in 0..8 {
             print!("|");
             for j in 0..8 {
-                print!(" {} |", Self::converter(self.get_piece(63 - ((i * 8) + (7 - j)))));
+                print!(" {} |", Self::converter(self.get_piece(Square::new(63 - ((i * 8) + (7 - j))))));
             }
-            println!("");
+            println!();
             println!(" --- --- --- --- --- --- --- ---");
         }
     }
 
+    /// The FEN letter of the piece, upper case for White, or `_` for an empty square.
     pub fn converter(piece: Piece) -> char {
         let chr: char = match piece.typ {
             PieceType::Pawn   => 'p',
@@ -62,52 +112,37 @@ impl Board {
         }
     }
 
-    pub fn print_move(mov: &Move) -> String {
-        let promotion: char = match mov.promotion {
-            PieceType::Rook   => 'r',
-            PieceType::Knight => 'n',
-            PieceType::Bishop => 'b',
-            PieceType::Queen  => 'q',
-            _ => ' ',
-        };
-        format!(
-            "{}{}{}",
-            Self::square_to_string(mov.start_square),
-            Self::square_to_string(mov.end_square),
-            promotion
-        )
-    }
-
+    /// The piece on the square, `EMPTY_PIECE` if there is none.
     pub fn get_piece(&self, square: Square) -> Piece {
         let color: Color =
-            if self.white_pieces & (1 << square) > 0 {
+            if self.white_pieces.contains(square) {
                 Color::White
-            } else if self.black_pieces & (1 << square) > 0 {
+            } else if self.black_pieces.contains(square) {
                 Color::Black
             } else {
                 Color::Empty
             };
 
         let typ: PieceType =
-            if self.pawns & (1 << square) > 0 {
+            if self.pawns.contains(square) {
                 PieceType::Pawn
-            } else if self.rooks & (1 << square) > 0 {
+            } else if self.rooks.contains(square) {
                 PieceType::Rook
-            } else if self.knights & (1 << square) > 0 {
+            } else if self.knights.contains(square) {
                 PieceType::Knight
-            } else if self.bishops & (1 << square) > 0 {
+            } else if self.bishops.contains(square) {
                 PieceType::Bishop
-            } else if self.queens & (1 << square) > 0 {
+            } else if self.queens.contains(square) {
                 PieceType::Queen
-            } else if self.kings & (1 << square) > 0 {
+            } else if self.kings.contains(square) {
                 PieceType::King
             } else {
                 PieceType::Empty
             };
 
-        return Piece {
-            typ: typ,
-            color: color,
+        Piece {
+            typ,
+            color,
         }
     }
 
@@ -119,8 +154,9 @@ impl Board {
         }
     }
 
+    /// Moves the piece on the start square to the empty end square, without any other change to the position.
     pub fn move_piece(&mut self, start_square: Square, end_square: Square) {
-        let from_to_bb = (1 << start_square) ^ (1 << end_square);
+        let from_to_bb = start_square.bitboard() ^ end_square.bitboard();
 
         let piece = self.get_piece(start_square);
         
@@ -141,32 +177,33 @@ impl Board {
         }
     }
 
+    /// Plays a pseudo-legal move, see `is_legal` for whether it leaves the own king in check.
     pub fn make_move(&mut self, mov: Move) {
         let last_en_passant = self.en_passant;
         let last_castling_rights = self.castling_rights;
         let last_halfmove_clock = self.halfmove_clock;
 
-        let Move {start_square, end_square, promotion} = mov;
+        let (start_square, end_square, promotion) = (mov.start_square(), mov.end_square(), mov.promotion());
         
         let start_piece = self.get_piece(start_square);
         let end_piece   = self.get_piece(end_square);
 
-        let mut en_passant_capture = (64, EMPTY_PIECE);
+        let mut en_passant_capture = None;
 
         if end_piece != EMPTY_PIECE {
             match end_piece.color {
-                Color::White => self.white_pieces ^= 1 << end_square,
-                Color::Black => self.black_pieces ^= 1 << end_square,
+                Color::White => self.white_pieces ^= end_square.bitboard(),
+                Color::Black => self.black_pieces ^= end_square.bitboard(),
                 Color::Empty => panic!("Tried to capture an empty piece!"),
             }
             
             match end_piece.typ {
-                PieceType::Pawn   => self.pawns   ^= 1 << end_square,
-                PieceType::Rook   => self.rooks   ^= 1 << end_square,
-                PieceType::Knight => self.knights ^= 1 << end_square,
-                PieceType::Bishop => self.bishops ^= 1 << end_square,
-                PieceType::Queen  => self.queens  ^= 1 << end_square,
-                PieceType::King   => self.kings   ^= 1 << end_square,
+                PieceType::Pawn   => self.pawns   ^= end_square.bitboard(),
+                PieceType::Rook   => self.rooks   ^= end_square.bitboard(),
+                PieceType::Knight => self.knights ^= end_square.bitboard(),
+                PieceType::Bishop => self.bishops ^= end_square.bitboard(),
+                PieceType::Queen  => self.queens  ^= end_square.bitboard(),
+                PieceType::King   => self.kings   ^= end_square.bitboard(),
                 PieceType::Empty  => {self.print_board(); println!("{:?} {:?} {:?}", mov, start_piece, end_piece); panic!("Tried to capture an empty piece!")},
             }
         }
@@ -174,56 +211,52 @@ impl Board {
         self.move_piece(start_square, end_square);
 
         if promotion != PieceType::Empty {
-            self.pawns ^= 1 << end_square;
+            self.pawns ^= end_square.bitboard();
             match promotion {
-                PieceType::Rook   => self.rooks   ^= 1 << end_square,
-                PieceType::Knight => self.knights ^= 1 << end_square,
-                PieceType::Bishop => self.bishops ^= 1 << end_square,
-                PieceType::Queen  => self.queens  ^= 1 << end_square,
+                PieceType::Rook   => self.rooks   ^= end_square.bitboard(),
+                PieceType::Knight => self.knights ^= end_square.bitboard(),
+                PieceType::Bishop => self.bishops ^= end_square.bitboard(),
+                PieceType::Queen  => self.queens  ^= end_square.bitboard(),
                 _  => panic!("Tried to promote to an invalid piece!"),
             }
         }
 
         // En passant capture
-        if start_piece.typ == PieceType::Pawn && end_piece == EMPTY_PIECE 
-            && (start_square % 8) as i32 - (end_square % 8) as i32 != 0 {
+        if mov.is_en_passant() {
             let enemy_pos = 
                 if start_piece.color == Color::White {
-                    end_square - 8
+                    end_square.offset(-8)
                 } else {
-                    end_square + 8
+                    end_square.offset(8)
                 };
 
             let enemy_piece = self.get_piece(enemy_pos);
 
-            self.pawns ^= 1 << enemy_pos;
+            self.pawns ^= enemy_pos.bitboard();
             
             match start_piece.color {
-                Color::White => self.black_pieces ^= 1 << enemy_pos,
-                Color::Black => self.white_pieces ^= 1 << enemy_pos,
+                Color::White => self.black_pieces ^= enemy_pos.bitboard(),
+                Color::Black => self.white_pieces ^= enemy_pos.bitboard(),
                 Color::Empty => panic!("Tried to capture an empty piece!"),
             }
 
-            en_passant_capture = (enemy_pos, enemy_piece);
+            en_passant_capture = Some((enemy_pos, enemy_piece));
         }
 
         // En passant detection
         if start_piece.typ == PieceType::Pawn && i32::abs(start_square as i32 - end_square as i32) == 16 {
-            self.en_passant = (start_square + end_square) / 2;
+            self.en_passant = Some(Square::new((start_square as usize + end_square as usize) / 2));
         } else {
-            self.en_passant = 64;
+            self.en_passant = None;
         }
 
         // Castling
-        if start_piece.typ == PieceType::King && i32::abs(start_square as i32 - end_square as i32) == 2 {
-            if end_square == 2 {
-                self.move_piece(0, 3);
-            } else if end_square == 6 {
-                self.move_piece(7, 5);
-            } else if end_square == 62 {
-                self.move_piece(63, 61);
-            } else {
-                self.move_piece(56, 59);
+        if mov.is_castle() {
+            match end_square {
+                Square::C1 => self.move_piece(Square::A1, Square::D1),
+                Square::G1 => self.move_piece(Square::H1, Square::F1),
+                Square::G8 => self.move_piece(Square::H8, Square::F8),
+                _          => self.move_piece(Square::A8, Square::D8),
             }
         }
 
@@ -241,22 +274,31 @@ impl Board {
         // Removing castling rights: Moving a rook
         if start_piece.typ == PieceType::Rook {
             if start_piece.color == Color::White {
-                if start_square == 7 {
+                if start_square == Square::H1 {
                     self.castling_rights.0.0 = false;
                 }
-                if start_square == 0 {
+                if start_square == Square::A1 {
                     self.castling_rights.0.1 = false;
                 }
             } else {
-                if start_square == 63 {
+                if start_square == Square::H8 {
                     self.castling_rights.1.0 = false;
                 }
-                if start_square == 56 {
+                if start_square == Square::A8 {
                     self.castling_rights.1.1 = false;
                 }
             }
         }
 
+        // Removing castling rights: Capturing a rook
+        match end_square {
+            Square::H1 => self.castling_rights.0.0 = false,
+            Square::A1 => self.castling_rights.0.1 = false,
+            Square::H8 => self.castling_rights.1.0 = false,
+            Square::A8 => self.castling_rights.1.1 = false,
+            _          => (),
+        }
+
         // Halfmove-clock
         if start_piece.typ == PieceType::Pawn || end_piece != EMPTY_PIECE {
             self.halfmove_clock = 0;
@@ -265,9 +307,14 @@ impl Board {
         }
 
         self.change_turn();
+
+        #[cfg(feature = "nnue")]
+        self.update_accumulators(&mov, &start_piece, &end_piece, &en_passant_capture);
+
         self.moves.push((last_en_passant, last_castling_rights, last_halfmove_clock, end_piece, en_passant_capture));
     }
 
+    /// Takes back the last move made, which has to be `mov`.
     pub fn unmake_move(&mut self, mov: Move) {
         let captured_piece;
         let en_passant_capture;
@@ -278,11 +325,11 @@ impl Board {
             captured_piece,
             en_passant_capture,
         ) = self.moves.pop().unwrap();
-        let Move {start_square, end_square, promotion} = mov;
+        let (start_square, end_square, promotion) = (mov.start_square(), mov.end_square(), mov.promotion());
         
         let piece = self.get_piece(end_square);
 
-        let from_to_bb = (1 << start_square) ^ (1 << end_square);
+        let from_to_bb = start_square.bitboard() ^ end_square.bitboard();
 
         match piece.color {
             Color::White => self.white_pieces ^= from_to_bb,
@@ -303,56 +350,76 @@ impl Board {
             }
         // Piece has promoted
         } else {
-            self.pawns ^= 1 << start_square;
+            self.pawns ^= start_square.bitboard();
             match piece.typ {
-                PieceType::Rook   => self.rooks   ^= 1 << end_square,
-                PieceType::Knight => self.knights ^= 1 << end_square,
-                PieceType::Bishop => self.bishops ^= 1 << end_square,
-                PieceType::Queen  => self.queens  ^= 1 << end_square,
+                PieceType::Rook   => self.rooks   ^= end_square.bitboard(),
+                PieceType::Knight => self.knights ^= end_square.bitboard(),
+                PieceType::Bishop => self.bishops ^= end_square.bitboard(),
+                PieceType::Queen  => self.queens  ^= end_square.bitboard(),
                 _ => panic!("Tried to remove an empty piece!"),
             }
         }
          
         if captured_piece != EMPTY_PIECE {
             match captured_piece.color {
-                Color::White => self.white_pieces ^= 1 << end_square,
-                Color::Black => self.black_pieces ^= 1 << end_square,
+                Color::White => self.white_pieces ^= end_square.bitboard(),
+                Color::Black => self.black_pieces ^= end_square.bitboard(),
                 Color::Empty => panic!("Tried to restore an empty piece!"),
             }
             
             match captured_piece.typ {
-                PieceType::Pawn   => self.pawns   ^= 1 << end_square,
-                PieceType::Rook   => self.rooks   ^= 1 << end_square,
-                PieceType::Knight => self.knights ^= 1 << end_square,
-                PieceType::Bishop => self.bishops ^= 1 << end_square,
-                PieceType::Queen  => self.queens  ^= 1 << end_square,
-                PieceType::King   => self.kings   ^= 1 << end_square,
+                PieceType::Pawn   => self.pawns   ^= end_square.bitboard(),
+                PieceType::Rook   => self.rooks   ^= end_square.bitboard(),
+                PieceType::Knight => self.knights ^= end_square.bitboard(),
+                PieceType::Bishop => self.bishops ^= end_square.bitboard(),
+                PieceType::Queen  => self.queens  ^= end_square.bitboard(),
+                PieceType::King   => self.kings   ^= end_square.bitboard(),
                 PieceType::Empty  => panic!("Tried to restore an empty piece!"),
             }
         }
 
-        if en_passant_capture.1 != EMPTY_PIECE {
-            self.pawns ^= 1 << en_passant_capture.0;
-            match en_passant_capture.1.color {
-                Color::White => self.white_pieces ^= 1 << en_passant_capture.0,
-                Color::Black => self.black_pieces ^= 1 << en_passant_capture.0,
+        if let Some((square, captured_pawn)) = en_passant_capture {
+            self.pawns ^= square.bitboard();
+            match captured_pawn.color {
+                Color::White => self.white_pieces ^= square.bitboard(),
+                Color::Black => self.black_pieces ^= square.bitboard(),
                 Color::Empty => panic!("Tried to restore an empty piece!"),
             }
         }
 
-        if piece.typ == PieceType::King && i32::abs(start_square as i32 - end_square as i32) == 2 {
-            if end_square == 2 {
-                self.move_piece(3, 0);
-            } else if end_square == 6 {
-                self.move_piece(5, 7);
-            } else if end_square == 62 {
-                self.move_piece(61, 63);
-            } else {
-                self.move_piece(59, 56);
+        if mov.is_castle() {
+            match end_square {
+                Square::C1 => self.move_piece(Square::D1, Square::A1),
+                Square::G1 => self.move_piece(Square::F1, Square::H1),
+                Square::G8 => self.move_piece(Square::F8, Square::H8),
+                _          => self.move_piece(Square::D8, Square::A8),
             }
         }
 
 
         self.change_turn();
+
+        #[cfg(feature = "nnue")]
+        self.accumulators.pop();
+    }
+
+    /// Passes the turn, used by null-move pruning
+    pub fn make_null_move(&mut self) {
+        self.moves.push((self.en_passant, self.castling_rights, self.halfmove_clock, EMPTY_PIECE, None));
+        self.en_passant = None;
+        self.halfmove_clock += 1;
+        self.change_turn();
+
+        #[cfg(feature = "nnue")]
+        self.accumulators.duplicate();
+    }
+
+    /// Takes back a null move.
+    pub fn unmake_null_move(&mut self) {
+        (self.en_passant, self.castling_rights, self.halfmove_clock, _, _) = self.moves.pop().unwrap();
+        self.change_turn();
+
+        #[cfg(feature = "nnue")]
+        self.accumulators.pop();
     }
 }
@@ -1,32 +1,44 @@
+//! Constant positions, moves and bitboards used throughout the engine.
 use crate::types::*;
 use crate::board::Board;
+#[cfg(feature = "nnue")]
+use crate::nnue::AccumulatorStack;
 
-pub const EMPTY_MOVE: Move = Move {
-    start_square: 64,
-    end_square: 64,
-    promotion: PieceType::Empty,
-};
+/// Stands for no move. It is never generated, as a move has to change the square of its piece
+pub const EMPTY_MOVE: Move = Move::new(Square::A1, Square::A1, Move::QUIET);
 
+/// What get_piece returns for an empty square
 pub const EMPTY_PIECE: Piece = Piece {
     typ: PieceType::Empty,
     color: Color::Empty,
 };
 
+/// A board without pieces, White to move. Positions are set up from it with load_fen
 pub const EMPTY_BOARD: Board = Board {
-    white_pieces: 0,
-    black_pieces: 0,
-    pawns: 0,
-    rooks: 0,
-    knights: 0,
-    bishops: 0,
-    queens: 0,
-    kings: 0,
+    white_pieces: Bitboard::EMPTY,
+    black_pieces: Bitboard::EMPTY,
+    pawns: Bitboard::EMPTY,
+    rooks: Bitboard::EMPTY,
+    knights: Bitboard::EMPTY,
+    bishops: Bitboard::EMPTY,
+    queens: Bitboard::EMPTY,
+    kings: Bitboard::EMPTY,
 
     turn: Color::White,
     castling_rights: ((false, false), (false, false)),
-    en_passant: 0,
+    en_passant: None,
     halfmove_clock: 0,
     fullmove_clock: 0,
     
     moves: Vec::new(),
+
+    #[cfg(feature = "nnue")]
+    accumulators: AccumulatorStack::new(),
 };
+
+/// The squares of the a-file
+pub const FILE_A: Bitboard = File::A.bitboard();
+/// The squares of the h-file
+pub const FILE_H: Bitboard = File::H.bitboard();
+/// The light squares, b1, a2 and so on
+pub const LIGHT_SQUARES: Bitboard = Bitboard(0x55aa55aa55aa55aa);
@@ -0,0 +1,278 @@
+//! The datagen subcommand, generating training positions from self-play games.
+use crate::board::Board;
+use crate::types::*;
+use crate::consts::*;
+use crate::options::SearchOptions;
+use crate::search::{self, SearchState};
+use crate::transposition_table::TranspositionTable;
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_POSITIONS: usize = 100_000;
+const DEFAULT_NODES: u64 = 5_000;
+const HASH_MEGABYTES: usize = 1;
+const START_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+const RANDOM_PLIES: usize = 8;
+const MAX_GAME_PLIES: usize = 400;
+// A game is given to the side the search has favoured by this much for several plies in a row
+const ADJUDICATION_SCORE: i32 = 2_000;
+const ADJUDICATION_PLIES: usize = 6;
+// Positions further from equal than this say little about the evaluation
+const MAX_RECORDED_SCORE: i32 = 1_500;
+
+// Each record is 32 bytes, all little endian:
+// occupancy (u64), one nibble per occupied square in square order (16 bytes),
+// side to move (u8), castling rights (u8, KQkq in bits 0..4), en passant square (u8, 64 = none),
+// halfmove clock (u8), score from White (i16), result from White (u8, 0 loss, 1 draw, 2 win), padding (u8)
+const RECORD_SIZE: usize = 32;
+
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+struct Position {
+    key: u64,
+    fen: String,
+    record: [u8; RECORD_SIZE],
+}
+
+struct Output {
+    text: BufWriter<File>,
+    binary: BufWriter<File>,
+    keys: HashSet<u64>,
+    written: usize,
+}
+
+fn piece_nibble(piece: &Piece) -> u8 {
+    let typ = match piece.typ {
+        PieceType::Pawn   => 0,
+        PieceType::Rook   => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Queen  => 4,
+        _                 => 5,
+    };
+    if piece.color == Color::White { typ } else { typ + 8 }
+}
+
+fn pack(board: &Board, score: i32) -> [u8; RECORD_SIZE] {
+    let mut record = [0; RECORD_SIZE];
+    let occupancy = board.white_pieces | board.black_pieces;
+    record[0..8].copy_from_slice(&occupancy.0.to_le_bytes());
+
+    for (index, square) in occupancy.enumerate() {
+        record[8 + index / 2] |= piece_nibble(&board.get_piece(square)) << (4 * (index % 2));
+    }
+
+    let ((white_king_side, white_queen_side), (black_king_side, black_queen_side)) = board.castling_rights;
+    record[24] = if board.turn == Color::White { 0 } else { 1 };
+    record[25] = white_king_side as u8 | (white_queen_side as u8) << 1 | (black_king_side as u8) << 2 | (black_queen_side as u8) << 3;
+    record[26] = board.en_passant.map_or(64, |square| square as u8);
+    record[27] = board.halfmove_clock;
+    record[28..30].copy_from_slice(&(score as i16).to_le_bytes());
+    record
+}
+
+fn has_insufficient_material(board: &Board) -> bool {
+    (board.pawns | board.rooks | board.queens).is_empty()
+        && (board.knights | board.bishops).count() <= 1
+}
+
+// Plays one game and returns the recorded positions with the result from White, 0.0, 0.5 or 1.0
+fn play_game(random: &mut Random, state: &mut SearchState, nodes: u64) -> (Vec<Position>, f64) {
+    let mut board = EMPTY_BOARD;
+    board.load_fen(START_POSITION.to_string());
+
+    for _ in 0..RANDOM_PLIES {
+        let moves = board.generate_legal_moves();
+        if moves.is_empty() {
+            return (Vec::new(), 0.5);
+        }
+        board.make_move(moves[random.below(moves.len())]);
+    }
+
+    let mut positions = Vec::new();
+    let mut repetitions = HashMap::new();
+    let mut adjudication = (0, 0);
+
+    for _ in 0..MAX_GAME_PLIES {
+        let key = board.zobrist_key();
+        let legal_moves = board.generate_legal_moves();
+        if legal_moves.is_empty() {
+            let result = match (board.in_check(), board.turn) {
+                (false, _) => 0.5,
+                (true, Color::White) => 0.0,
+                (true, _) => 1.0,
+            };
+            return (positions, result);
+        }
+
+        let seen = repetitions.entry(key).or_insert(0);
+        *seen += 1;
+        if *seen >= 3 || board.halfmove_clock >= 100 || has_insufficient_material(&board) {
+            return (positions, 0.5);
+        }
+
+        let result = search::search_nodes(nodes, &mut board, state);
+        let (mov, score) = match result.first() {
+            Some((mov, score)) if legal_moves.contains(mov) => (*mov, *score),
+            _ => (legal_moves[0], 0),
+        };
+
+        let side = score.signum();
+        if score.abs() >= ADJUDICATION_SCORE && side == adjudication.0 {
+            adjudication.1 += 1;
+        } else {
+            adjudication = (side, 1);
+        }
+        if score.abs() >= ADJUDICATION_SCORE && adjudication.1 >= ADJUDICATION_PLIES {
+            return (positions, if side > 0 { 1.0 } else { 0.0 });
+        }
+
+        if !mov.is_capture() && !mov.is_promotion() && !board.in_check() && score.abs() < MAX_RECORDED_SCORE {
+            positions.push(Position {
+                key,
+                fen: board.to_fen(),
+                record: pack(&board, score),
+            });
+        }
+
+        board.make_move(mov);
+    }
+
+    (positions, 0.5)
+}
+
+// Keys and count of a previous run, so an interrupted run picks up where it stopped
+fn load_existing(path: &str) -> (HashSet<u64>, usize) {
+    let mut keys = HashSet::new();
+    let mut written = 0;
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return (keys, written),
+    };
+
+    let mut board = EMPTY_BOARD;
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        if let Some(fen) = line.split('|').next() {
+            if fen.split_whitespace().count() == 6 {
+                board.load_fen(fen.trim().to_string());
+                keys.insert(board.zobrist_key());
+                written += 1;
+            }
+        }
+    }
+    (keys, written)
+}
+
+fn open_append(path: &str) -> std::io::Result<BufWriter<File>> {
+    Ok(BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?))
+}
+
+fn write_game(output: &Mutex<Output>, positions: Vec<Position>, result: f64, target: usize) -> std::io::Result<usize> {
+    let mut output = output.lock().unwrap();
+    let result_byte = (result * 2.0) as u8;
+
+    for mut position in positions {
+        if output.written >= target || !output.keys.insert(position.key) {
+            continue;
+        }
+        position.record[30] = result_byte;
+        let score = i16::from_le_bytes([position.record[28], position.record[29]]);
+
+        writeln!(output.text, "{} | {} | {:.1}", position.fen, score, result)?;
+        output.binary.write_all(&position.record)?;
+        output.written += 1;
+    }
+    output.text.flush()?;
+    output.binary.flush()?;
+    Ok(output.written)
+}
+
+pub fn datagen(arguments: &[String]) {
+    if arguments.is_empty() {
+        println!("Usage: datagen <output> [positions] [threads] [nodes]");
+        return;
+    }
+    let target = arguments.get(1).and_then(|positions| positions.parse().ok()).unwrap_or(DEFAULT_POSITIONS);
+    let threads = arguments
+        .get(2)
+        .and_then(|threads| threads.parse().ok())
+        .unwrap_or_else(|| thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1));
+    let nodes = arguments.get(3).and_then(|nodes| nodes.parse().ok()).unwrap_or(DEFAULT_NODES);
+
+    let text_path = format!("{}.txt", arguments[0]);
+    let binary_path = format!("{}.bin", arguments[0]);
+    let (keys, written) = load_existing(&text_path);
+    if written > 0 {
+        println!("Resuming with {written} positions in {text_path}");
+    }
+    if Path::new(&binary_path).exists() && std::fs::metadata(&binary_path).map(|data| data.len()).unwrap_or(0) != (written * RECORD_SIZE) as u64 {
+        println!("{binary_path} does not match {text_path}, delete both to start over");
+        return;
+    }
+
+    let (text, binary) = match (open_append(&text_path), open_append(&binary_path)) {
+        (Ok(text), Ok(binary)) => (text, binary),
+        (Err(error), _) | (_, Err(error)) => {
+            println!("Could not open output: {error}");
+            return;
+        }
+    };
+    let output = Mutex::new(Output { text, binary, keys, written });
+    let finished = AtomicBool::new(written >= target);
+    let resumed = written;
+
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_nanos() as u64).unwrap_or(0);
+    let start = Instant::now();
+
+    thread::scope(|scope| {
+        for thread in 0..threads {
+            let output = &output;
+            let finished = &finished;
+            scope.spawn(move || {
+                let mut random = Random(seed ^ (thread as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15) | 1);
+                // Every position is searched from scratch, a small table is quick to clear
+                let mut state = SearchState::new(SearchOptions {
+                    transposition_table: Arc::new(TranspositionTable::new(HASH_MEGABYTES)),
+                    ..SearchOptions::default()
+                });
+
+                while !finished.load(Ordering::Relaxed) {
+                    let (positions, result) = play_game(&mut random, &mut state, nodes);
+                    match write_game(output, positions, result, target) {
+                        Ok(written) if written >= target => finished.store(true, Ordering::Relaxed),
+                        Ok(written) => if thread == 0 {
+                            println!("{written} positions, {:.0} positions/s", (written - resumed) as f64 / start.elapsed().as_secs_f64());
+                        },
+                        Err(error) => {
+                            println!("Could not write output: {error}");
+                            finished.store(true, Ordering::Relaxed);
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    println!("{} positions written to {text_path} and {binary_path}", output.lock().unwrap().written);
+}
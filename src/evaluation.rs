@@ -0,0 +1,181 @@
+//! The handcrafted evaluation: material, piece-square tables, pawn structure, mobility and king safety,
+//! tapered between middlegame and endgame.
+use crate::board::Board;
+use crate::types::*;
+use crate::consts::*;
+use crate::attack_bitboards::*;
+use crate::piece_square_tables::*;
+use crate::pawn_structure::{self, PawnHashTable};
+use crate::mobility;
+use crate::king_safety;
+
+/// The game phase with all the pieces on the board
+pub const TOTAL_PHASE: i32 = 24;
+
+/// Goes from TOTAL_PHASE with all pieces on the board down to 0 in a pawn endgame
+pub fn game_phase(board: &Board) -> i32 {
+    let phase = (board.knights | board.bishops).count() as i32
+        + board.rooks.count() as i32 * 2
+        + board.queens.count() as i32 * 4;
+    i32::min(phase, TOTAL_PHASE)
+}
+
+/// Blends the middlegame and endgame values of the score by game phase
+pub fn taper(score: Score, phase: i32) -> i32 {
+    (score.mg * phase + score.eg * (TOTAL_PHASE - phase)) / TOTAL_PHASE
+}
+
+/// The rank of the square counted from the color's own side, 0 for its first rank
+pub fn relative_rank(square: Square, color: Color) -> usize {
+    square.rank().relative(color) as usize
+}
+
+/// The number of king moves between the squares
+pub fn distance(square1: Square, square2: Square) -> i32 {
+    let file_distance = i32::abs(square1.file() as i32 - square2.file() as i32);
+    let rank_distance = i32::abs(square1.rank() as i32 - square2.rank() as i32);
+    i32::max(file_distance, rank_distance)
+}
+
+/// The pieces of the color and those of the other side
+//                                              Own, Enemy
+pub fn side_bitboards(board: &Board, color: Color) -> (Bitboard, Bitboard) {
+    if color == Color::White {
+        (board.white_pieces, board.black_pieces)
+    } else {
+        (board.black_pieces, board.white_pieces)
+    }
+}
+
+/// The squares right next to the squares of the set on the same rank
+pub fn neighbours(bitboard: Bitboard) -> Bitboard {
+    ((bitboard & !FILE_H) << 1) | ((bitboard & !FILE_A) >> 1)
+}
+
+/// The squares in front of the square on its file, seen from the color
+pub fn front_span(square: Square, color: Color) -> Bitboard {
+    if color == Color::White {
+        NORTH_RAYS[square as usize]
+    } else {
+        SOUTH_RAYS[square as usize]
+    }
+}
+
+/// Indexed by PieceType, kings are never traded so they are left out
+pub const PIECE_VALUES: [i32; 7] = [0, 100, 500, 320, 330, 900, 0];
+
+/// The evaluation terms in the order trace returns them
+pub const TERM_NAMES: [&str; 7] = [
+    "Material",
+    "Piece-square",
+    "Pawn structure",
+    "Passed pawns",
+    "Mobility",
+    "Activity",
+    "King safety",
+];
+
+/// The piece-square table value of the piece on the square
+pub fn piece_square_value(piece: &Piece, square: Square) -> Score {
+    let table = |white_table: &[i32; 64], black_table: &[i32; 64]| {
+        if piece.color == Color::White {
+            white_table[square as usize]
+        } else {
+            black_table[square as usize]
+        }
+    };
+    let value = match piece.typ {
+        PieceType::Pawn   => table(&WHITE_PAWN, &BLACK_PAWN),
+        PieceType::Rook   => table(&WHITE_ROOK, &BLACK_ROOK),
+        PieceType::Knight => table(&WHITE_KNIGHT, &BLACK_KNIGHT),
+        PieceType::Bishop => table(&WHITE_BISHOP, &BLACK_BISHOP),
+        PieceType::Queen  => table(&WHITE_QUEEN, &BLACK_QUEEN),
+        PieceType::King   => return Score::new(
+            table(&WHITE_KING, &BLACK_KING),
+            table(&WHITE_KING_END_GAME, &BLACK_KING_END_GAME),
+        ),
+        PieceType::Empty  => 0,
+    };
+    Score::new(value, value)
+}
+
+/// The material and piece-square values of the color's pieces
+//                                                          Material, Piece-square
+pub fn evaluate_pieces_side(board: &Board, color: Color) -> (Score, Score) {
+    let (pieces, _) = side_bitboards(board, color);
+    let mut material = Score::default();
+    let mut piece_square = Score::default();
+
+    for square in pieces {
+        let piece = board.get_piece(square);
+        let value = PIECE_VALUES[piece.typ as usize];
+        material += Score::new(value, value);
+        piece_square += piece_square_value(&piece, square);
+    }
+
+    (material, piece_square)
+}
+
+/// The static evaluation in centipawns, positive when White is better.
+pub fn evaluate(board: &Board, pawn_hash_table: &mut PawnHashTable) -> i32 {
+    let (white_material, white_piece_square) = evaluate_pieces_side(board, Color::White);
+    let (black_material, black_piece_square) = evaluate_pieces_side(board, Color::Black);
+
+    let score = white_material + white_piece_square - black_material - black_piece_square
+        + pawn_structure::evaluate_pawns(board, pawn_hash_table)
+        + mobility::evaluate_mobility(board)
+        + king_safety::evaluate_king_safety(board);
+
+    taper(score, game_phase(board))
+}
+
+/// Every term of the evaluation, untapered, for each side
+//                                                 Term, White, Black
+pub fn trace(board: &Board) -> Vec<(&'static str, Score, Score)> {
+    let side = |color: Color| {
+        let (material, piece_square) = evaluate_pieces_side(board, color);
+        let (pawn_structure, passed_pawns) = pawn_structure::evaluate_pawn_side(board, color);
+        let (mobility, activity) = mobility::evaluate_mobility_side(board, color);
+        [
+            material,
+            piece_square,
+            pawn_structure,
+            pawn_structure::evaluate_passed_pawns(board, color, passed_pawns),
+            mobility,
+            activity,
+            king_safety::evaluate_king_safety_side(board, color),
+        ]
+    };
+
+    let white = side(Color::White);
+    let black = side(Color::Black);
+    (0..TERM_NAMES.len())
+        .map(|term| (TERM_NAMES[term], white[term], black[term]))
+        .collect()
+}
+
+/// Prints the trace as a table, with the tapered total
+pub fn print_trace(board: &Board) {
+    let terms = trace(board);
+    let phase = game_phase(board);
+
+    println!("      Term      |    White    |    Black    |    Total");
+    println!("                |   MG    EG  |   MG    EG  |   MG    EG");
+    println!("----------------+-------------+-------------+-------------");
+
+    let mut total = Score::default();
+    for (name, white, black) in terms {
+        let difference = white - black;
+        total += difference;
+        println!(
+            "{:>15} | {:>5} {:>5} | {:>5} {:>5} | {:>5} {:>5}",
+            name, white.mg, white.eg, black.mg, black.eg, difference.mg, difference.eg
+        );
+    }
+
+    println!("----------------+-------------+-------------+-------------");
+    println!("{:>15} |             |             | {:>5} {:>5}", "Total", total.mg, total.eg);
+    println!();
+    println!("Phase: {phase} / {TOTAL_PHASE}");
+    println!("Final evaluation: {} (White side)", taper(total, phase));
+}
@@ -1,8 +1,11 @@
+//! Loading a position from FEN.
+
 use crate::types::*;
 use crate::consts::*;
 use crate::board::Board;
 
 impl Board {
+    /// Replaces the position with the one in `fen`, which needs all six fields.
     pub fn load_fen(&mut self, fen: String) {
         *self = EMPTY_BOARD;
         let mut parts = fen.split(" ");
@@ -12,40 +15,41 @@ impl Board {
         let en_passant = parts.next().unwrap();
         let halfmove_clock = parts.next().unwrap();
         let fullmove_clock = parts.next().unwrap();
-        let mut pos: Square = 56;
+        let mut pos: usize = 56;
 
         for piece in pieces.chars() {
             if piece == '/' {
                 continue;
-            } else if piece.is_digit(10) {
-                pos += piece as Square - '0' as Square;
+            } else if piece.is_ascii_digit() {
+                pos += piece as usize - '0' as usize;
             } else {
+                let square = Square::new(pos).bitboard();
                 if piece.is_uppercase() {
-                    self.white_pieces |= 1 << pos;
+                    self.white_pieces |= square;
                     match piece {
-                        'P' => self.pawns   |= 1 << pos,
-                        'R' => self.rooks   |= 1 << pos,
-                        'N' => self.knights |= 1 << pos,
-                        'B' => self.bishops |= 1 << pos,
-                        'Q' => self.queens  |= 1 << pos,
-                        'K' => self.kings   |= 1 << pos,
+                        'P' => self.pawns   |= square,
+                        'R' => self.rooks   |= square,
+                        'N' => self.knights |= square,
+                        'B' => self.bishops |= square,
+                        'Q' => self.queens  |= square,
+                        'K' => self.kings   |= square,
                         _ => (),
                     }
                 } else {
-                    self.black_pieces |= 1 << pos;
+                    self.black_pieces |= square;
                     match piece {
-                        'p' => self.pawns   |= 1 << pos,
-                        'r' => self.rooks   |= 1 << pos,
-                        'n' => self.knights |= 1 << pos,
-                        'b' => self.bishops |= 1 << pos,
-                        'q' => self.queens  |= 1 << pos,
-                        'k' => self.kings   |= 1 << pos,
+                        'p' => self.pawns   |= square,
+                        'r' => self.rooks   |= square,
+                        'n' => self.knights |= square,
+                        'b' => self.bishops |= square,
+                        'q' => self.queens  |= square,
+                        'k' => self.kings   |= square,
                         _ => (),
                     }
                 }
                 pos += 1;
             }
-            if pos > 8 && pos % 8 == 0 {
+            if pos > 8 && pos.is_multiple_of(8) {
                 pos -= 16;
             }
         }
@@ -65,13 +69,56 @@ impl Board {
             }
         }
 
-        if en_passant != "-" {
-            self.en_passant = Board::string_to_square(en_passant.to_string());
-        } else {
-            self.en_passant = 64;
-        }
+        self.en_passant = en_passant.parse::<Square>().ok();
 
         self.halfmove_clock = halfmove_clock.parse::<Clock>().unwrap();
         self.fullmove_clock = fullmove_clock.parse::<Clock>().unwrap();
     }
+
+    /// The position in Forsyth-Edwards Notation.
+    pub fn to_fen(&self) -> String {
+        let mut pieces = String::new();
+        for rank in Rank::ALL.into_iter().rev() {
+            let mut empty = 0;
+            for file in File::ALL {
+                let piece = self.get_piece(Square::from_file_rank(file, rank));
+                if piece == EMPTY_PIECE {
+                    empty += 1;
+                } else {
+                    if empty > 0 {
+                        pieces += &empty.to_string();
+                        empty = 0;
+                    }
+                    pieces.push(Board::converter(piece));
+                }
+            }
+            if empty > 0 {
+                pieces += &empty.to_string();
+            }
+            if rank != Rank::R1 {
+                pieces.push('/');
+            }
+        }
+
+        let turn = if self.turn == Color::White { "w" } else { "b" };
+
+        let mut castling = String::new();
+        for (right, chr) in [
+            (self.castling_rights.0.0, 'K'),
+            (self.castling_rights.0.1, 'Q'),
+            (self.castling_rights.1.0, 'k'),
+            (self.castling_rights.1.1, 'q'),
+        ] {
+            if right {
+                castling.push(chr);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = self.en_passant.map_or("-".to_string(), |square| square.to_string());
+
+        format!("{} {} {} {} {} {}", pieces, turn, castling, en_passant, self.halfmove_clock, self.fullmove_clock)
+    }
 }
@@ -0,0 +1,165 @@
+//! King safety evaluation: attack units of the pieces near the king, safe checks and the pawn shield.
+
+use crate::board::Board;
+use crate::types::*;
+use crate::attack_bitboards::{KNIGHT_ATTACK_BITBOARDS, KING_ATTACK_BITBOARDS};
+use crate::evaluation::*;
+use crate::move_generator::pawn_attacks;
+
+const KING_DANGER_SIZE: usize = 100;
+
+const fn generate_king_danger() -> [i32; KING_DANGER_SIZE] {
+    let mut table = [0; KING_DANGER_SIZE];
+    let mut units = 0;
+    while units < KING_DANGER_SIZE {
+        let danger = (units * units) as i32 / 4;
+        table[units] = if danger < 500 { danger } else { 500 };
+        units += 1;
+    }
+    table
+}
+
+// Indexed by attack units, grows quadratically so that several attackers count for more than their sum
+const KING_DANGER: [i32; KING_DANGER_SIZE] = generate_king_danger();
+
+const KNIGHT_ATTACK_UNITS: i32 = 2;
+const BISHOP_ATTACK_UNITS: i32 = 2;
+const ROOK_ATTACK_UNITS: i32 = 3;
+const QUEEN_ATTACK_UNITS: i32 = 5;
+
+const KNIGHT_SAFE_CHECK_UNITS: i32 = 3;
+const BISHOP_SAFE_CHECK_UNITS: i32 = 2;
+const ROOK_SAFE_CHECK_UNITS: i32 = 4;
+const QUEEN_SAFE_CHECK_UNITS: i32 = 3;
+
+// Indexed by the number of ranks between the king and the closest pawn on a file
+const PAWN_SHIELD: [Score; 8] = [
+    Score::new(0, 0), Score::new(15, 0), Score::new(8, 0), Score::new(0, 0),
+    Score::new(0, 0), Score::new(0, 0), Score::new(0, 0), Score::new(0, 0),
+];
+const PAWN_STORM: [Score; 8] = [
+    Score::new(0, 0), Score::new(-5, 0), Score::new(-25, -5), Score::new(-12, -2),
+    Score::new(0, 0), Score::new(0, 0), Score::new(0, 0), Score::new(0, 0),
+];
+const MISSING_SHIELD_PAWN: Score = Score::new(-15, 0);
+const OPEN_FILE_NEAR_KING: Score = Score::new(-20, 0);
+const SEMI_OPEN_FILE_NEAR_KING: Score = Score::new(-10, 0);
+
+fn closest_pawn(pawns: Bitboard, color: Color) -> Option<Square> {
+    if pawns.is_empty() {
+        None
+    } else if color == Color::White {
+        Some(pawns.lsb())
+    } else {
+        Some(pawns.msb())
+    }
+}
+
+fn evaluate_pawn_shelter(board: &Board, color: Color, king_square: Square) -> Score {
+    let (own_pieces, enemy_pieces) = side_bitboards(board, color);
+    let own_pawns = board.pawns & own_pieces;
+    let enemy_pawns = board.pawns & enemy_pieces;
+    let king_file = king_square.file() as usize;
+    let king_rank = king_square.rank();
+
+    let mut score = Score::default();
+
+    for file in File::ALL[king_file.saturating_sub(1)..=usize::min(king_file + 1, 7)].iter().copied() {
+        let file_bitboard = file.bitboard();
+        let front = front_span(Square::from_file_rank(file, king_rank), color);
+
+        match closest_pawn(own_pawns & front, color) {
+            Some(square) => score += PAWN_SHIELD[(square.rank() as usize).abs_diff(king_rank as usize)],
+            None => score += MISSING_SHIELD_PAWN,
+        }
+
+        if let Some(square) = closest_pawn(enemy_pawns & front, color) {
+            score += PAWN_STORM[(square.rank() as usize).abs_diff(king_rank as usize)];
+        }
+
+        if (board.pawns & file_bitboard).is_empty() {
+            score += OPEN_FILE_NEAR_KING;
+        } else if (own_pawns & file_bitboard).is_empty() {
+            score += SEMI_OPEN_FILE_NEAR_KING;
+        }
+    }
+
+    score
+}
+
+fn attacked_squares(board: &Board, color: Color) -> Bitboard {
+    let (own_pieces, _) = side_bitboards(board, color);
+    let mut attacks = pawn_attacks(board.pawns & own_pieces, color);
+
+    for square in own_pieces & !board.pawns {
+        attacks |= match board.get_piece(square).typ {
+            PieceType::Knight => KNIGHT_ATTACK_BITBOARDS[square as usize],
+            PieceType::Bishop => board.bishop_attacks(square),
+            PieceType::Rook   => board.rook_attacks(square),
+            PieceType::Queen  => board.queen_attacks(square),
+            PieceType::King   => KING_ATTACK_BITBOARDS[square as usize],
+            _ => Bitboard::EMPTY,
+        };
+    }
+
+    attacks
+}
+
+pub fn evaluate_king_safety_side(board: &Board, color: Color) -> Score {
+    let (own_pieces, enemy_pieces) = side_bitboards(board, color);
+    let enemy_color = if color == Color::White { Color::Black } else { Color::White };
+    let kings = board.kings & own_pieces;
+    if kings.is_empty() {
+        return Score::default();
+    }
+    let king_square = kings.lsb();
+
+    let mut king_zone = KING_ATTACK_BITBOARDS[king_square as usize] | kings;
+    king_zone |= if color == Color::White { king_zone << 8 } else { king_zone >> 8 };
+
+    let own_attacks = attacked_squares(board, color);
+    let safe_squares = !own_attacks & !enemy_pieces;
+    let knight_checks = KNIGHT_ATTACK_BITBOARDS[king_square as usize] & safe_squares;
+    let bishop_checks = board.bishop_attacks(king_square) & safe_squares;
+    let rook_checks = board.rook_attacks(king_square) & safe_squares;
+
+    let mut attackers = 0;
+    let mut attack_units = 0;
+
+    for square in enemy_pieces & !board.pawns & !board.kings {
+        let (attacks, zone_units, check_units, checks) = match board.get_piece(square).typ {
+            PieceType::Knight => (KNIGHT_ATTACK_BITBOARDS[square as usize], KNIGHT_ATTACK_UNITS, KNIGHT_SAFE_CHECK_UNITS, knight_checks),
+            PieceType::Bishop => (board.bishop_attacks(square), BISHOP_ATTACK_UNITS, BISHOP_SAFE_CHECK_UNITS, bishop_checks),
+            PieceType::Rook   => (board.rook_attacks(square), ROOK_ATTACK_UNITS, ROOK_SAFE_CHECK_UNITS, rook_checks),
+            PieceType::Queen  => (board.queen_attacks(square), QUEEN_ATTACK_UNITS, QUEEN_SAFE_CHECK_UNITS, bishop_checks | rook_checks),
+            _ => continue,
+        };
+
+        let zone_attacks = (attacks & king_zone).count() as i32;
+        if zone_attacks > 0 {
+            attackers += 1;
+            attack_units += zone_units * zone_attacks;
+        }
+        attack_units += check_units * (attacks & checks).count() as i32;
+    }
+
+    // Weaker pawn cover lets the attack through more easily
+    if (pawn_attacks(board.pawns & own_pieces, color) & king_zone).is_empty() {
+        attack_units += 3;
+    }
+    if !(pawn_attacks(board.pawns & enemy_pieces, enemy_color) & king_zone).is_empty() {
+        attack_units += 2;
+    }
+
+    let mut score = evaluate_pawn_shelter(board, color, king_square);
+    if attackers >= 2 || (attackers == 1 && !(board.queens & enemy_pieces).is_empty()) {
+        let danger = KING_DANGER[usize::min(attack_units as usize, KING_DANGER_SIZE - 1)];
+        score -= Score::new(danger, danger / 8);
+    }
+
+    score
+}
+
+pub fn evaluate_king_safety(board: &Board) -> Score {
+    evaluate_king_safety_side(board, Color::White) - evaluate_king_safety_side(board, Color::Black)
+}
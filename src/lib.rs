@@ -0,0 +1,67 @@
+//! A bitboard chess engine, usable as a library or through the `chess_v3` UCI binary.
+//!
+//! - [`board::Board`] holds a position. It reads and writes FEN (`load_fen`, `to_fen`), reads and writes
+//!   standard algebraic notation (`from_san`, `to_san`), generates moves (`generate_moves`,
+//!   `generate_legal_moves`) and plays them (`make_move`, `unmake_move`).
+//! - [`search::search`] finds the best move within [`limits::SearchLimits`], using the settings and
+//!   transposition table in [`options::SearchOptions`].
+//! - [`evaluation::evaluate`] scores a position statically, in centipawns from White's point of view.
+//! - [`perft`] counts the leaf nodes of the move tree, for testing move generation.
+//!
+//! The UCI loop and the subcommands of the engine binary (`uci`, `bench`, `datagen`, `tuner` and
+//! `perftdiff`) are public for the binary's sake only and left out of this documentation.
+//!
+//! ```
+//! use chess_v3::board::Board;
+//! use chess_v3::consts::EMPTY_BOARD;
+//! use chess_v3::limits::SearchLimits;
+//! use chess_v3::options::SearchOptions;
+//! use chess_v3::search::{self, SearchControl};
+//!
+//! let mut board = EMPTY_BOARD;
+//! board.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
+//! assert_eq!(board.generate_legal_moves().len(), 20);
+//!
+//! let e4 = board.from_san("e4").unwrap();
+//! board.make_move(e4);
+//! assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+//!
+//! let limits = SearchLimits { depth: Some(4), ..SearchLimits::default() };
+//! let runtime = tokio::runtime::Runtime::new().unwrap();
+//! let result = runtime.block_on(search::search(&limits, &mut board, &SearchOptions::default(), &SearchControl::silent()));
+//! assert!(board.generate_legal_moves().contains(&result.best_move));
+//! println!("{}", result.best_move);
+//! ```
+#![warn(missing_docs)]
+
+pub mod board;
+pub mod consts;
+pub mod types;
+mod fen_reader;
+mod san;
+pub mod move_generator;
+mod attack_bitboards;
+pub mod search;
+pub mod move_ordering;
+pub mod transposition_table;
+mod piece_square_tables;
+mod zobrist;
+pub mod evaluation;
+pub mod pawn_structure;
+mod mobility;
+mod king_safety;
+#[doc(hidden)]
+pub mod tuner;
+#[doc(hidden)]
+pub mod datagen;
+pub mod options;
+pub mod limits;
+#[doc(hidden)]
+pub mod bench;
+pub mod perft;
+#[doc(hidden)]
+pub mod perftdiff;
+#[doc(hidden)]
+pub mod uci;
+#[cfg(feature = "nnue")]
+pub mod nnue;
@@ -0,0 +1,93 @@
+//! The limits of a search as given by the UCI go command: depth, nodes, time and the clock.
+use crate::board::Board;
+use crate::types::*;
+
+// Kept back from the clock for the time it takes the move to reach the GUI
+const MOVE_OVERHEAD: u64 = 50;
+// The remaining time is split over this many moves when the GUI does not say
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+/// The limits of one "go" command, the search ends at whichever is reached first
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// The deepest iteration searched
+    pub depth: Option<i32>,
+    /// The time for this move in milliseconds
+    pub movetime: Option<u64>,
+    /// The nodes searched by all threads together
+    pub nodes: Option<u64>,
+    /// Mate in this many moves
+    pub mate: Option<i32>,
+    /// Only these root moves are searched, every legal move if empty
+    pub search_moves: Vec<Move>,
+    /// White's clock in milliseconds
+    pub white_time: Option<u64>,
+    /// Black's clock in milliseconds
+    pub black_time: Option<u64>,
+    /// White's increment per move in milliseconds
+    pub white_increment: u64,
+    /// Black's increment per move in milliseconds
+    pub black_increment: u64,
+    /// The moves until the next time control
+    pub moves_to_go: Option<u64>,
+    /// No time limit, and the result waits for "stop" even if the search finishes
+    pub infinite: bool,
+    /// The search starts on the expected reply and waits for "ponderhit" or "stop"
+    pub ponder: bool,
+}
+
+fn parse_value<'a, T: std::str::FromStr>(name: &str, arguments: &mut impl Iterator<Item = &'a str>) -> Result<T, String> {
+    let value = arguments.next().ok_or(format!("{name} needs a value"))?;
+    value.parse().map_err(|_| format!("Invalid {name} value: {value}"))
+}
+
+impl SearchLimits {
+    /// Expects the arguments of "go", the search moves are read in the position of `board`
+    pub fn parse(arguments: &[&str], board: &Board) -> Result<Self, String> {
+        let mut limits = SearchLimits::default();
+        let mut arguments = arguments.iter().map(|argument| argument.trim()).filter(|argument| !argument.is_empty()).peekable();
+
+        while let Some(argument) = arguments.next() {
+            match argument {
+                "depth" => limits.depth = Some(parse_value(argument, &mut arguments)?),
+                "movetime" => limits.movetime = Some(parse_value(argument, &mut arguments)?),
+                "nodes" => limits.nodes = Some(parse_value(argument, &mut arguments)?),
+                "mate" => limits.mate = Some(parse_value(argument, &mut arguments)?),
+                "wtime" => limits.white_time = Some(parse_value(argument, &mut arguments)?),
+                "btime" => limits.black_time = Some(parse_value(argument, &mut arguments)?),
+                "winc" => limits.white_increment = parse_value(argument, &mut arguments)?,
+                "binc" => limits.black_increment = parse_value(argument, &mut arguments)?,
+                "movestogo" => limits.moves_to_go = Some(parse_value(argument, &mut arguments)?),
+                "infinite" => limits.infinite = true,
+                "ponder" => limits.ponder = true,
+                // Takes every following argument that is a move
+                "searchmoves" => {
+                    while let Some(mov) = arguments.peek().and_then(|argument| board.string_to_move(argument)) {
+                        limits.search_moves.push(mov);
+                        arguments.next();
+                    }
+                },
+                x => return Err(format!("{x} is either not implemented or not a valid argument for \"go\"")),
+            }
+        }
+        Ok(limits)
+    }
+
+    /// In milliseconds, None when the search has no time limit
+    pub fn time(&self, color: Color) -> Option<u64> {
+        if self.infinite {
+            return None;
+        }
+
+        let (clock, increment) = if color == Color::White {
+            (self.white_time, self.white_increment)
+        } else {
+            (self.black_time, self.black_increment)
+        };
+        let clock_time = clock.map(|time| {
+            let moves_to_go = self.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+            (time / moves_to_go + increment * 3 / 4).min(time.saturating_sub(MOVE_OVERHEAD)).max(1)
+        });
+        [self.movetime, clock_time].into_iter().flatten().min()
+    }
+}
@@ -0,0 +1,118 @@
+//! Mobility and piece placement evaluation: attacked squares, bishop pair, outposts and rooks on open files.
+
+use crate::board::Board;
+use crate::types::*;
+use crate::consts::*;
+use crate::attack_bitboards::KNIGHT_ATTACK_BITBOARDS;
+use crate::evaluation::*;
+use crate::move_generator::pawn_attacks;
+
+// Indexed by the number of reachable squares in the mobility area
+const KNIGHT_MOBILITY: [Score; 9] = [
+    Score::new(-30, -40), Score::new(-15, -25), Score::new(-5, -10), Score::new(0, -5),
+    Score::new(5, 5), Score::new(10, 10), Score::new(15, 15), Score::new(20, 18),
+    Score::new(25, 20),
+];
+const BISHOP_MOBILITY: [Score; 14] = [
+    Score::new(-25, -40), Score::new(-10, -20), Score::new(5, -5), Score::new(10, 5),
+    Score::new(18, 12), Score::new(25, 20), Score::new(30, 28), Score::new(33, 32),
+    Score::new(36, 36), Score::new(40, 40), Score::new(42, 44), Score::new(45, 46),
+    Score::new(48, 48), Score::new(50, 50),
+];
+const ROOK_MOBILITY: [Score; 15] = [
+    Score::new(-30, -40), Score::new(-15, -15), Score::new(-5, 0), Score::new(-3, 10),
+    Score::new(0, 20), Score::new(3, 30), Score::new(6, 40), Score::new(10, 45),
+    Score::new(13, 50), Score::new(16, 55), Score::new(18, 60), Score::new(20, 65),
+    Score::new(22, 68), Score::new(24, 70), Score::new(26, 72),
+];
+const QUEEN_MOBILITY: [Score; 28] = [
+    Score::new(-20, -30), Score::new(-10, -20), Score::new(-5, -10), Score::new(-2, -5),
+    Score::new(0, 0), Score::new(2, 5), Score::new(4, 10), Score::new(6, 15),
+    Score::new(8, 20), Score::new(10, 25), Score::new(12, 30), Score::new(14, 34),
+    Score::new(16, 38), Score::new(18, 42), Score::new(20, 46), Score::new(22, 50),
+    Score::new(24, 53), Score::new(26, 56), Score::new(28, 59), Score::new(30, 62),
+    Score::new(31, 64), Score::new(32, 66), Score::new(33, 67), Score::new(34, 68),
+    Score::new(35, 69), Score::new(36, 70), Score::new(37, 71), Score::new(38, 72),
+];
+
+const BISHOP_PAIR: Score = Score::new(25, 45);
+const BAD_BISHOP_PAWN: Score = Score::new(-3, -5);
+const KNIGHT_OUTPOST: Score = Score::new(25, 10);
+const ROOK_OPEN_FILE: Score = Score::new(40, 15);
+const ROOK_SEMI_OPEN_FILE: Score = Score::new(20, 8);
+const ROOK_ON_SEVENTH: Score = Score::new(10, 25);
+
+//                                                           Mobility, Activity
+pub fn evaluate_mobility_side(board: &Board, color: Color) -> (Score, Score) {
+    let (own_pieces, enemy_pieces) = side_bitboards(board, color);
+    let enemy_color = if color == Color::White { Color::Black } else { Color::White };
+    let own_pawns = board.pawns & own_pieces;
+    let enemy_pawns = board.pawns & enemy_pieces;
+    let own_pawn_attacks = pawn_attacks(own_pawns, color);
+    let mobility_area = !pawn_attacks(enemy_pawns, enemy_color) & !(own_pawns | (board.kings & own_pieces));
+
+    let mut mobility = Score::default();
+    let mut activity = Score::default();
+
+    for square in board.knights & own_pieces {
+        let attacks = KNIGHT_ATTACK_BITBOARDS[square as usize] & mobility_area;
+        mobility += KNIGHT_MOBILITY[attacks.count() as usize];
+
+        let rank = relative_rank(square, color);
+        if (3..=5).contains(&rank)
+            && own_pawn_attacks.contains(square)
+            && (enemy_pawns & neighbours(front_span(square, color))).is_empty() {
+            activity += KNIGHT_OUTPOST;
+        }
+    }
+
+    let bishops = board.bishops & own_pieces;
+    if !(bishops & LIGHT_SQUARES).is_empty() && !(bishops & !LIGHT_SQUARES).is_empty() {
+        activity += BISHOP_PAIR;
+    }
+
+    for square in bishops {
+        let attacks = board.bishop_attacks(square) & mobility_area;
+        mobility += BISHOP_MOBILITY[attacks.count() as usize];
+
+        let same_colored_squares = if LIGHT_SQUARES.contains(square) {
+            LIGHT_SQUARES
+        } else {
+            !LIGHT_SQUARES
+        };
+        activity += BAD_BISHOP_PAWN * (own_pawns & same_colored_squares).count() as i32;
+    }
+
+    let seventh_rank = Rank::R7.relative(color).bitboard();
+    let eighth_rank = Rank::R8.relative(color).bitboard();
+
+    for square in board.rooks & own_pieces {
+        let attacks = board.rook_attacks(square) & mobility_area;
+        mobility += ROOK_MOBILITY[attacks.count() as usize];
+
+        let file = square.file().bitboard();
+        if (board.pawns & file).is_empty() {
+            activity += ROOK_OPEN_FILE;
+        } else if (own_pawns & file).is_empty() {
+            activity += ROOK_SEMI_OPEN_FILE;
+        }
+
+        if seventh_rank.contains(square)
+            && (!(enemy_pawns & seventh_rank).is_empty() || !(board.kings & enemy_pieces & eighth_rank).is_empty()) {
+            activity += ROOK_ON_SEVENTH;
+        }
+    }
+
+    for square in board.queens & own_pieces {
+        let attacks = board.queen_attacks(square) & mobility_area;
+        mobility += QUEEN_MOBILITY[attacks.count() as usize];
+    }
+
+    (mobility, activity)
+}
+
+pub fn evaluate_mobility(board: &Board) -> Score {
+    let (white_mobility, white_activity) = evaluate_mobility_side(board, Color::White);
+    let (black_mobility, black_activity) = evaluate_mobility_side(board, Color::Black);
+    white_mobility + white_activity - black_mobility - black_activity
+}
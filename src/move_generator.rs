@@ -1,273 +1,394 @@
+//! Pseudo-legal and legal move generation, attack sets and check detection.
 use crate::board::*;
 use crate::types::*;
 use crate::attack_bitboards::*;
+use crate::consts::*;
+
+/// The squares attacked by pawns of the color standing on the given squares
+pub fn pawn_attacks(pawns: Bitboard, color: Color) -> Bitboard {
+    match color {
+        Color::White => ((pawns & !FILE_A) << 7) | ((pawns & !FILE_H) << 9),
+        Color::Black => ((pawns & !FILE_A) >> 9) | ((pawns & !FILE_H) >> 7),
+        Color::Empty => Bitboard::EMPTY,
+    }
+}
+
+fn positive_ray_attacks(rays: &[Bitboard; 64], square: Square, occupied: Bitboard) -> Bitboard {
+    let intersection = occupied & rays[square as usize];
+    if intersection.is_empty() {
+        rays[square as usize]
+    } else {
+        rays[square as usize] ^ rays[intersection.lsb() as usize]
+    }
+}
+
+fn negative_ray_attacks(rays: &[Bitboard; 64], square: Square, occupied: Bitboard) -> Bitboard {
+    let intersection = occupied & rays[square as usize];
+    if intersection.is_empty() {
+        rays[square as usize]
+    } else {
+        rays[square as usize] ^ rays[intersection.msb() as usize]
+    }
+}
+
+/// Takes the occupancy as an argument, so pieces can be lifted off the board without changing it
+pub fn rook_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    positive_ray_attacks(&EAST_RAYS, square, occupied)
+        | positive_ray_attacks(&NORTH_RAYS, square, occupied)
+        | negative_ray_attacks(&WEST_RAYS, square, occupied)
+        | negative_ray_attacks(&SOUTH_RAYS, square, occupied)
+}
+
+/// Takes the occupancy as an argument, like rook_attacks
+pub fn bishop_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    positive_ray_attacks(&NORTH_EAST_RAYS, square, occupied)
+        | positive_ray_attacks(&NORTH_WEST_RAYS, square, occupied)
+        | negative_ray_attacks(&SOUTH_WEST_RAYS, square, occupied)
+        | negative_ray_attacks(&SOUTH_EAST_RAYS, square, occupied)
+}
+
+// The squares strictly between two squares that share a rank, file or diagonal, and none otherwise
+fn squares_between(a: Square, b: Square) -> Bitboard {
+    let ends = a.bitboard() | b.bitboard();
+    if a.file() == b.file() || a.rank() == b.rank() {
+        rook_attacks(a, ends) & rook_attacks(b, ends)
+    } else if bishop_attacks(a, ends).contains(b) {
+        bishop_attacks(a, ends) & bishop_attacks(b, ends)
+    } else {
+        Bitboard::EMPTY
+    }
+}
 
 impl Board {
-    pub fn generate_moves(&self) -> Vec<Move> {
-
-        let mut result: Vec<Move> = Vec::new();
-
-        result.extend(self.generate_pawn_moves());
-        result.extend(self.generate_rook_moves());
-        result.extend(self.generate_knight_moves());
-        result.extend(self.generate_bishop_moves());
-        result.extend(self.generate_queen_moves());
-        result.extend(self.generate_king_moves());
-        
-        result
+    /// Every pseudo-legal move of the side to move, some of which may leave its king in check.
+    pub fn generate_moves(&self) -> MoveList {
+        self.generate_moves_of_kind(GenType::All)
     }
 
-    pub fn generate_positive_ray_moves(&self, rays: [u64; 64], square: Square, flipped_own_pieces: u64) -> u64 {
-        let occupied = self.white_pieces | self.black_pieces;
-        let intersection = occupied & rays[square];
-        if intersection == 0 {
-            rays[square]
-        } else {
-            (rays[square] ^ rays[intersection.trailing_zeros() as Square]) & flipped_own_pieces
+    /// The pseudo-legal moves of one kind, in the same order as they appear in generate_moves.
+    pub fn generate_moves_of_kind(&self, kind: GenType) -> MoveList {
+        let mut moves = MoveList::new();
+        let (own_pieces, enemy_pieces) = self.side_pieces();
+        let empty_squares = !(own_pieces | enemy_pieces);
+
+        let (targets, king_targets) = match kind {
+            GenType::All => (!own_pieces, !own_pieces),
+            GenType::Captures => (enemy_pieces, enemy_pieces),
+            GenType::Quiets | GenType::QuietChecks => (empty_squares, empty_squares),
+            GenType::Evasions => {
+                let checkers = self.checkers();
+                if checkers.is_empty() {
+                    return self.generate_moves_of_kind(GenType::All);
+                }
+                // Against a double check only the king can move
+                let blocks = if checkers.count() > 1 {
+                    Bitboard::EMPTY
+                } else {
+                    let king = (self.kings & own_pieces).lsb();
+                    checkers | squares_between(king, checkers.lsb())
+                };
+                (blocks, !own_pieces)
+            },
+        };
+
+        self.generate_pawn_moves(&mut moves, kind, targets);
+        self.generate_rook_moves(&mut moves, targets);
+        self.generate_knight_moves(&mut moves, targets);
+        self.generate_bishop_moves(&mut moves, targets);
+        self.generate_queen_moves(&mut moves, targets);
+        self.generate_king_moves(&mut moves, king_targets, matches!(kind, GenType::All | GenType::Quiets | GenType::QuietChecks));
+
+        if kind == GenType::QuietChecks {
+            moves.retain(|mov| self.gives_check(mov));
+        }
+        moves
+    }
+
+    /// Every legal move of the side to move.
+    pub fn generate_legal_moves(&mut self) -> MoveList {
+        let mut moves = self.generate_moves();
+        moves.retain(|mov| self.is_legal(mov));
+        moves
+    }
+
+    /// Whether a pseudo-legal move keeps the own king out of check, and does not castle out of or through check.
+    pub fn is_legal(&mut self, mov: &Move) -> bool {
+        let color = self.turn;
+        let enemy_color = if color == Color::White { Color::Black } else { Color::White };
+
+        // The king may not castle out of or through check
+        if mov.is_castle()
+            && (self.is_square_attacked(mov.start_square(), enemy_color)
+                || self.is_square_attacked(Square::new((mov.start_square() as usize + mov.end_square() as usize) / 2), enemy_color)) {
+            return false;
         }
+
+        self.make_move(*mov);
+        let own_pieces = if color == Color::White { self.white_pieces } else { self.black_pieces };
+        let kings = self.kings & own_pieces;
+        let legal = kings.is_empty() || !self.is_square_attacked(kings.lsb(), enemy_color);
+        self.unmake_move(*mov);
+        legal
     }
 
-    pub fn generate_negative_ray_moves(&self, rays: [u64; 64], square: Square, flipped_own_pieces: u64) -> u64 {
-        let occupied = self.white_pieces | self.black_pieces;
-        let intersection = occupied & rays[square];
-        if intersection == 0 {
-            rays[square]
+    /// Whether any piece of the color attacks the square.
+    pub fn is_square_attacked(&self, square: Square, by: Color) -> bool {
+        let (attackers, defender) = if by == Color::White {
+            (self.white_pieces, Color::Black)
         } else {
-            (rays[square] ^ rays[intersection.leading_zeros() as Square ^ 63]) & flipped_own_pieces
+            (self.black_pieces, Color::White)
+        };
+
+        !(pawn_attacks(square.bitboard(), defender) & self.pawns & attackers).is_empty()
+            || !(KNIGHT_ATTACK_BITBOARDS[square as usize] & self.knights & attackers).is_empty()
+            || !(KING_ATTACK_BITBOARDS[square as usize] & self.kings & attackers).is_empty()
+            || !(self.bishop_attacks(square) & (self.bishops | self.queens) & attackers).is_empty()
+            || !(self.rook_attacks(square) & (self.rooks | self.queens) & attackers).is_empty()
+    }
+
+    /// Every piece of either color that attacks the square, with the given pieces in the way.
+    pub fn attackers_to(&self, square: Square, occupied: Bitboard) -> Bitboard {
+        (pawn_attacks(square.bitboard(), Color::Black) & self.pawns & self.white_pieces
+            | pawn_attacks(square.bitboard(), Color::White) & self.pawns & self.black_pieces
+            | KNIGHT_ATTACK_BITBOARDS[square as usize] & self.knights
+            | KING_ATTACK_BITBOARDS[square as usize] & self.kings
+            | bishop_attacks(square, occupied) & (self.bishops | self.queens)
+            | rook_attacks(square, occupied) & (self.rooks | self.queens))
+            & occupied
+    }
+
+    /// The enemy pieces giving check to the king of the side to move.
+    pub fn checkers(&self) -> Bitboard {
+        let (own_pieces, enemy_pieces) = self.side_pieces();
+        let kings = self.kings & own_pieces;
+        if kings.is_empty() {
+            return Bitboard::EMPTY;
+        }
+        self.attackers_to(kings.lsb(), own_pieces | enemy_pieces) & enemy_pieces
+    }
+
+    /// Whether a pseudo-legal move checks the enemy king, directly or by uncovering a slider, without making it.
+    pub fn gives_check(&self, mov: &Move) -> bool {
+        let (own_pieces, enemy_pieces) = self.side_pieces();
+        let enemy_kings = self.kings & enemy_pieces;
+        if enemy_kings.is_empty() {
+            return false;
+        }
+        let king = enemy_kings.lsb();
+        let (start_square, end_square) = (mov.start_square(), mov.end_square());
+
+        let mut moved = start_square.bitboard();
+        let mut occupied = (own_pieces | enemy_pieces) ^ moved | end_square.bitboard();
+        if mov.is_en_passant() {
+            occupied ^= end_square.offset(if self.turn == Color::White { -8 } else { 8 }).bitboard();
+        }
+
+        let piece = if mov.is_promotion() { mov.promotion() } else { self.get_piece(start_square).typ };
+        let direct = match piece {
+            PieceType::Pawn => pawn_attacks(end_square.bitboard(), self.turn),
+            PieceType::Knight => KNIGHT_ATTACK_BITBOARDS[end_square as usize],
+            PieceType::Bishop => bishop_attacks(end_square, occupied),
+            PieceType::Rook => rook_attacks(end_square, occupied),
+            PieceType::Queen => rook_attacks(end_square, occupied) | bishop_attacks(end_square, occupied),
+            PieceType::King => KING_ATTACK_BITBOARDS[end_square as usize],
+            PieceType::Empty => Bitboard::EMPTY,
+        };
+        if direct.contains(king) {
+            return true;
+        }
+
+        // When castling, it is the rook that can give check
+        if mov.is_castle() {
+            let (rook_start, rook_end) = match end_square {
+                Square::G1 => (Square::H1, Square::F1),
+                Square::C1 => (Square::A1, Square::D1),
+                Square::G8 => (Square::H8, Square::F8),
+                _ => (Square::A8, Square::D8),
+            };
+            moved |= rook_start.bitboard();
+            occupied ^= rook_start.bitboard() | rook_end.bitboard();
+            if rook_attacks(rook_end, occupied).contains(king) {
+                return true;
+            }
         }
+
+        let sliders = own_pieces & !moved;
+        !(bishop_attacks(king, occupied) & (self.bishops | self.queens) & sliders).is_empty()
+            || !(rook_attacks(king, occupied) & (self.rooks | self.queens) & sliders).is_empty()
     }
 
-    pub fn generate_pawn_moves(&self) -> Vec<Move> {
-        let mut moves: Vec<Move> = Vec::new();
-        let own_pieces = if self.turn == Color::White {
-            self.white_pieces
+    /// Whether the side to move is in check.
+    pub fn in_check(&self) -> bool {
+        let (own_pieces, enemy_color) = if self.turn == Color::White {
+            (self.white_pieces, Color::Black)
         } else {
-            self.black_pieces
+            (self.black_pieces, Color::White)
         };
+        let kings = self.kings & own_pieces;
+        !kings.is_empty() && self.is_square_attacked(kings.lsb(), enemy_color)
+    }
+
+    /// The squares a rook on the square attacks, blocked by the pieces on the board.
+    pub fn rook_attacks(&self, square: Square) -> Bitboard {
+        rook_attacks(square, self.white_pieces | self.black_pieces)
+    }
+
+    /// The squares a bishop on the square attacks, blocked by the pieces on the board.
+    pub fn bishop_attacks(&self, square: Square) -> Bitboard {
+        bishop_attacks(square, self.white_pieces | self.black_pieces)
+    }
+
+    /// The squares a queen on the square attacks, blocked by the pieces on the board.
+    pub fn queen_attacks(&self, square: Square) -> Bitboard {
+        self.rook_attacks(square) | self.bishop_attacks(square)
+    }
+
+    //                                    Own,      Enemy
+    fn side_pieces(&self) -> (Bitboard, Bitboard) {
+        if self.turn == Color::White {
+            (self.white_pieces, self.black_pieces)
+        } else {
+            (self.black_pieces, self.white_pieces)
+        }
+    }
+
+    // One move to every target, flagged as a capture when it lands on an enemy piece
+    fn push_moves(moves: &mut MoveList, start_square: Square, targets: Bitboard, enemy_pieces: Bitboard) {
+        for end_square in targets {
+            let flags = if enemy_pieces.contains(end_square) { Move::CAPTURE } else { Move::QUIET };
+            moves.push(Move::new(start_square, end_square, flags));
+        }
+    }
+
+    // The targets only restrict the pawns for evasions, the other kinds are told apart by capture and promotion
+    fn generate_pawn_moves(&self, moves: &mut MoveList, kind: GenType, targets: Bitboard) {
+        let (own_pieces, enemy_pieces) = self.side_pieces();
 
         let pawns = own_pieces & self.pawns;
         let pieces = self.white_pieces | self.black_pieces;
-            
-        for start_square in 0..64 {
-            if pawns & (1 << start_square) > 0 {
-                let push_bitboard: u64;
-                let mut result: u64;
- 
-                if self.turn == Color::White {
-                    result = WHITE_PAWN_PUSHES[start_square] & !pieces;
-                    if start_square / 8 == 1 && result > 0 {
-                        result |= WHITE_PAWN_PUSHES[start_square + 8] & !pieces;
-                    }
-                } else {
-                    result = BLACK_PAWN_PUSHES[start_square] & !pieces;
-                    if start_square / 8 == 6 && result > 0 {
-                        result |= BLACK_PAWN_PUSHES[start_square - 8] & !pieces;
-                    }
+        let promotion_rank = Rank::R8.relative(self.turn);
+        let en_passant_bitboard = self.en_passant.map_or(Bitboard::EMPTY, Square::bitboard);
+
+        for start_square in pawns {
+            let mut push_bitboard: Bitboard;
+
+            if self.turn == Color::White {
+                push_bitboard = WHITE_PAWN_PUSHES[start_square as usize] & !pieces;
+                if start_square.rank() == Rank::R2 && !push_bitboard.is_empty() {
+                    push_bitboard |= WHITE_PAWN_PUSHES[start_square as usize + 8] & !pieces;
+                }
+            } else {
+                push_bitboard = BLACK_PAWN_PUSHES[start_square as usize] & !pieces;
+                if start_square.rank() == Rank::R7 && !push_bitboard.is_empty() {
+                    push_bitboard |= BLACK_PAWN_PUSHES[start_square as usize - 8] & !pieces;
                 }
-                push_bitboard = result;
+            }
 
-                let en_passant_bitboard = if self.en_passant < 64 {
-                    1 << self.en_passant
-                } else {
-                    0
+            let attacks = if self.turn == Color::White {
+                WHITE_PAWN_ATTACKS[start_square as usize]
+            } else {
+                BLACK_PAWN_ATTACKS[start_square as usize]
+            };
+
+            for end_square in push_bitboard | attacks & (enemy_pieces | en_passant_bitboard) {
+                let en_passant = en_passant_bitboard.contains(end_square);
+                let promotion = end_square.rank() == promotion_rank;
+                let tactical = enemy_pieces.contains(end_square) || en_passant || promotion;
+                let wanted = match kind {
+                    GenType::All => true,
+                    GenType::Captures => tactical,
+                    GenType::Quiets | GenType::QuietChecks => !tactical,
+                    // An en passant capture can also take the checking pawn off its square
+                    GenType::Evasions => targets.contains(end_square)
+                        || en_passant && targets.contains(end_square.offset(if self.turn == Color::White { -8 } else { 8 })),
                 };
+                if !wanted {
+                    continue;
+                }
 
-                let attack_bitboard = if self.turn == Color::White {
-                    WHITE_PAWN_ATTACKS[start_square] & (self.black_pieces | en_passant_bitboard)
-                } else {
-                    BLACK_PAWN_ATTACKS[start_square] & (self.white_pieces | en_passant_bitboard)
-                }; 
-
-                for end_square in 0..64 {
-                    let promote_bitboard: u64 = if self.turn == Color::White {
-                        0xff << 56
-                    } else {
-                        0xff
-                    };
-
-                    if push_bitboard & (1 << end_square) > 0 {
-                        if (1 << end_square) & promote_bitboard > 0 {
-                            for piece in [
-                                PieceType::Rook,
-                                PieceType::Knight,
-                                PieceType::Bishop,
-                                PieceType::Queen,
-                            ] {
-                                moves.push(Move::new(start_square, end_square, piece));
-                            }
-                        } else {
-                            moves.push(Move::new(start_square, end_square, PieceType::Empty));
-                        }
-                    } else if attack_bitboard & (1 << end_square) > 0 {
-                        if (1 << end_square) & promote_bitboard > 0 {
-                            for piece in [
-                                PieceType::Rook,
-                                PieceType::Knight,
-                                PieceType::Bishop,
-                                PieceType::Queen,
-                            ] {
-                                moves.push(Move::new(start_square, end_square, piece));
-                            }
-                        } else {
-                            moves.push(Move::new(start_square, end_square, PieceType::Empty));
-                        }
+                let capture = if enemy_pieces.contains(end_square) { Move::CAPTURE } else { Move::QUIET };
+                if promotion {
+                    for piece in [
+                        PieceType::Rook,
+                        PieceType::Knight,
+                        PieceType::Bishop,
+                        PieceType::Queen,
+                    ] {
+                        moves.push(Move::new(start_square, end_square, capture | Move::promotion_flags(piece)));
                     }
+                } else if en_passant {
+                    moves.push(Move::new(start_square, end_square, Move::EN_PASSANT));
+                } else {
+                    moves.push(Move::new(start_square, end_square, capture));
                 }
             }
         }
-        moves
     }
     
-    pub fn generate_rook_moves(&self) -> Vec<Move> {
-        let mut moves: Vec<Move> = Vec::new();
-        let own_pieces = if self.turn == Color::White {
-            self.white_pieces
-        } else {
-            self.black_pieces
-        };
+    fn generate_rook_moves(&self, moves: &mut MoveList, targets: Bitboard) {
+        let (own_pieces, enemy_pieces) = self.side_pieces();
 
-        let rooks = own_pieces & self.rooks;
-            
-        for start_square in 0..64 {
-            if rooks & (1 << start_square) > 0 {
-                let attack_bitboard = 
-                    self.generate_positive_ray_moves(EAST_RAYS, start_square, !own_pieces)
-                    | self.generate_positive_ray_moves(NORTH_RAYS, start_square, !own_pieces)
-                    | self.generate_negative_ray_moves(WEST_RAYS, start_square, !own_pieces)
-                    | self.generate_negative_ray_moves(SOUTH_RAYS, start_square, !own_pieces);
-                for end_square in 0..64 {
-                    if attack_bitboard & (1 << end_square) > 0 {
-                        moves.push(Move::new(start_square, end_square, PieceType::Empty));
-                    }
-                }
-            }
+        for start_square in own_pieces & self.rooks {
+            Self::push_moves(moves, start_square, self.rook_attacks(start_square) & targets, enemy_pieces);
         }
-        moves
     }
 
-    pub fn generate_knight_moves(&self) -> Vec<Move> {
-        let mut moves: Vec<Move> = Vec::new();
-        let own_pieces = if self.turn == Color::White {
-            self.white_pieces
-        } else {
-            self.black_pieces
-        };
+    fn generate_knight_moves(&self, moves: &mut MoveList, targets: Bitboard) {
+        let (own_pieces, enemy_pieces) = self.side_pieces();
 
-        let knights = own_pieces & self.knights;
-            
-        for start_square in 0..64 {
-            if knights & (1 << start_square) > 0 {
-                let attack_bitboard = KNIGHT_ATTACK_BITBOARDS[start_square] & !own_pieces;
-                for end_square in 0..64 {
-                    if attack_bitboard & (1 << end_square) > 0 {
-                        moves.push(Move::new(start_square, end_square, PieceType::Empty));
-                    }
-                }
-            }
+        for start_square in own_pieces & self.knights {
+            Self::push_moves(moves, start_square, KNIGHT_ATTACK_BITBOARDS[start_square as usize] & targets, enemy_pieces);
         }
-        moves
     }
 
-    pub fn generate_bishop_moves(&self) -> Vec<Move> {
-        let mut moves: Vec<Move> = Vec::new();
-        let own_pieces = if self.turn == Color::White {
-            self.white_pieces
-        } else {
-            self.black_pieces
-        };
+    fn generate_bishop_moves(&self, moves: &mut MoveList, targets: Bitboard) {
+        let (own_pieces, enemy_pieces) = self.side_pieces();
 
-        let bishops = own_pieces & self.bishops;
-            
-        for start_square in 0..64 {
-            if bishops & (1 << start_square) > 0 {
-                let attack_bitboard = 
-                    self.generate_positive_ray_moves(NORTH_EAST_RAYS, start_square, !own_pieces)
-                    | self.generate_positive_ray_moves(NORTH_WEST_RAYS, start_square, !own_pieces)
-                    | self.generate_negative_ray_moves(SOUTH_WEST_RAYS, start_square, !own_pieces)
-                    | self.generate_negative_ray_moves(SOUTH_EAST_RAYS, start_square, !own_pieces);
-                for end_square in 0..64 {
-                    if attack_bitboard & (1 << end_square) > 0 {
-                        moves.push(Move::new(start_square, end_square, PieceType::Empty));
-                    }
-                }
-            }
+        for start_square in own_pieces & self.bishops {
+            Self::push_moves(moves, start_square, self.bishop_attacks(start_square) & targets, enemy_pieces);
         }
-        moves
     }
 
-    pub fn generate_queen_moves(&self) -> Vec<Move> {
-        let mut moves: Vec<Move> = Vec::new();
-        let own_pieces = if self.turn == Color::White {
-            self.white_pieces
-        } else {
-            self.black_pieces
-        };
+    fn generate_queen_moves(&self, moves: &mut MoveList, targets: Bitboard) {
+        let (own_pieces, enemy_pieces) = self.side_pieces();
 
-        let queens = own_pieces & self.queens;
-            
-        for start_square in 0..64 {
-            if queens & (1 << start_square) > 0 {
-                let attack_bitboard = 
-                    self.generate_positive_ray_moves(EAST_RAYS, start_square, !own_pieces)
-                    | self.generate_positive_ray_moves(NORTH_RAYS, start_square, !own_pieces)
-                    | self.generate_negative_ray_moves(WEST_RAYS, start_square, !own_pieces)
-                    | self.generate_negative_ray_moves(SOUTH_RAYS, start_square, !own_pieces)
-                    | self.generate_positive_ray_moves(NORTH_EAST_RAYS, start_square, !own_pieces)
-                    | self.generate_positive_ray_moves(NORTH_WEST_RAYS, start_square, !own_pieces)
-                    | self.generate_negative_ray_moves(SOUTH_WEST_RAYS, start_square, !own_pieces)
-                    | self.generate_negative_ray_moves(SOUTH_EAST_RAYS, start_square, !own_pieces);
-                for end_square in 0..64 {
-                    if attack_bitboard & (1 << end_square) > 0 {
-                        moves.push(Move::new(start_square, end_square, PieceType::Empty));
-                    }
-                }
-            }
+        for start_square in own_pieces & self.queens {
+            Self::push_moves(moves, start_square, self.queen_attacks(start_square) & targets, enemy_pieces);
         }
-        moves
     }
 
-    pub fn generate_king_moves(&self) -> Vec<Move> {
-        let mut moves: Vec<Move> = Vec::new();
-        let own_pieces = if self.turn == Color::White {
-            self.white_pieces
-        } else {
-            self.black_pieces
-        };
+    fn generate_king_moves(&self, moves: &mut MoveList, targets: Bitboard, castling: bool) {
+        let (own_pieces, enemy_pieces) = self.side_pieces();
 
-        let kings = own_pieces & self.kings;
-            
-        for start_square in 0..64 {
-            if kings & (1 << start_square) > 0 {
-                let attack_bitboard = KING_ATTACK_BITBOARDS[start_square] & !own_pieces;
-                for end_square in 0..64 {
-                    if attack_bitboard & (1 << end_square) > 0 {
-                        moves.push(Move::new(start_square, end_square, PieceType::Empty));
-                    }
-                }
-            }
+        for start_square in own_pieces & self.kings {
+            Self::push_moves(moves, start_square, KING_ATTACK_BITBOARDS[start_square as usize] & targets, enemy_pieces);
         }
 
+        if !castling {
+            return;
+        }
+
+        let pieces = self.white_pieces | self.black_pieces;
         if self.turn == Color::White {
             if self.castling_rights.0.0
-                && 0b01100000 & (self.white_pieces | self.black_pieces) == 0 {
-                moves.push(Move::new(4, 6, PieceType::Empty));
+                && (pieces & Bitboard(0b01100000)).is_empty() {
+                moves.push(Move::new(Square::E1, Square::G1, Move::CASTLE));
             }
             
             if self.castling_rights.0.1
-                && 0b00001110 & (self.white_pieces | self.black_pieces) == 0 {
-                moves.push(Move::new(4, 2, PieceType::Empty));
+                && (pieces & Bitboard(0b00001110)).is_empty() {
+                moves.push(Move::new(Square::E1, Square::C1, Move::CASTLE));
             }
         } else {
             if self.castling_rights.1.0
-                && (0b01100000 << 56) & (self.white_pieces | self.black_pieces) == 0 {
-                moves.push(Move::new(60, 62, PieceType::Empty));
+                && (pieces & Bitboard(0b01100000 << 56)).is_empty() {
+                moves.push(Move::new(Square::E8, Square::G8, Move::CASTLE));
             }
             
             if self.castling_rights.1.1
-                && (0b00001110 << 56) & (self.white_pieces | self.black_pieces) == 0 {
-                moves.push(Move::new(60, 58, PieceType::Empty));
+                && (pieces & Bitboard(0b00001110 << 56)).is_empty() {
+                moves.push(Move::new(Square::E8, Square::C8, Move::CASTLE));
             }
         }
-        moves
     }
 }
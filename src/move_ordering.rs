@@ -0,0 +1,255 @@
+//! Orders the moves of a node for the search: transposition table move, captures by static exchange,
+//! killers, counter-moves and history.
+use crate::board::Board;
+use crate::types::*;
+use crate::consts::*;
+
+//                            Empty, Pawn, Rook, Knight, Bishop, Queen, King
+const SEE_VALUES: [i32; 7] = [0,     100,  500,  320,    330,    900,   20_000];
+
+/// History scores stay between -MAX_HISTORY and MAX_HISTORY
+pub const MAX_HISTORY: i32 = 16_384;
+
+/// Indexed by start and end square, one table per side
+pub type HistoryTable = [[i32; 64]; 64];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    TranspositionMove,
+    GoodCaptures,
+    Killers,
+    CounterMove,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+fn captured_piece(board: &Board, mov: &Move) -> PieceType {
+    if mov.is_en_passant() {
+        PieceType::Pawn
+    } else {
+        board.get_piece(mov.end_square()).typ
+    }
+}
+
+// Most valuable victim first, least valuable attacker breaking ties
+fn mvv_lva(board: &Board, mov: &Move) -> i32 {
+    let attacker = board.get_piece(mov.start_square()).typ;
+    SEE_VALUES[captured_piece(board, mov) as usize] * 10 + SEE_VALUES[mov.promotion() as usize] * 10
+        - SEE_VALUES[attacker as usize] / 10
+}
+
+fn least_valuable_attacker(board: &Board, attackers: Bitboard) -> (Square, PieceType) {
+    for (pieces, typ) in [
+        (board.pawns, PieceType::Pawn),
+        (board.knights, PieceType::Knight),
+        (board.bishops, PieceType::Bishop),
+        (board.rooks, PieceType::Rook),
+        (board.queens, PieceType::Queen),
+        (board.kings, PieceType::King),
+    ] {
+        if !(attackers & pieces).is_empty() {
+            return ((attackers & pieces).lsb(), typ);
+        }
+    }
+    unreachable!("No attacker in a non-empty attacker set")
+}
+
+/// Static exchange evaluation: the material the moving side wins if both sides keep recapturing
+/// on the end square with their least valuable piece, and may stop whenever that is better
+pub fn see(board: &Board, mov: &Move) -> i32 {
+    let target = mov.end_square();
+    let mut occupied = (board.white_pieces | board.black_pieces) ^ mov.start_square().bitboard();
+    if mov.is_en_passant() {
+        occupied ^= if board.turn == Color::White { target.offset(-8) } else { target.offset(8) }.bitboard();
+    }
+
+    let mut gains = [0; 32];
+    gains[0] = SEE_VALUES[captured_piece(board, mov) as usize];
+    let mut piece_on_target = board.get_piece(mov.start_square()).typ;
+    if mov.is_promotion() {
+        gains[0] += SEE_VALUES[mov.promotion() as usize] - SEE_VALUES[PieceType::Pawn as usize];
+        piece_on_target = mov.promotion();
+    }
+
+    let mut color = if board.turn == Color::White { Color::Black } else { Color::White };
+    let mut depth = 0;
+    while depth < gains.len() - 1 {
+        let own_pieces = if color == Color::White { board.white_pieces } else { board.black_pieces };
+        let attackers = board.attackers_to(target, occupied) & own_pieces;
+        if attackers.is_empty() {
+            break;
+        }
+        let (square, typ) = least_valuable_attacker(board, attackers);
+
+        depth += 1;
+        gains[depth] = SEE_VALUES[piece_on_target as usize] - gains[depth - 1];
+        if i32::max(-gains[depth - 1], gains[depth]) < 0 {
+            break;
+        }
+
+        occupied ^= square.bitboard();
+        piece_on_target = typ;
+        color = if color == Color::White { Color::Black } else { Color::White };
+    }
+
+    while depth > 0 {
+        gains[depth - 1] = -i32::max(-gains[depth - 1], gains[depth]);
+        depth -= 1;
+    }
+    gains[0]
+}
+
+fn take(moves: &mut Vec<(Move, i32)>, mov: &Move) -> bool {
+    match moves.iter().position(|(candidate, _)| candidate == mov) {
+        Some(index) => {
+            moves.swap_remove(index);
+            true
+        },
+        None => false,
+    }
+}
+
+fn take_best(moves: &mut Vec<(Move, i32)>) -> Option<Move> {
+    let (index, _) = moves.iter().enumerate().max_by_key(|(_, (_, score))| *score)?;
+    Some(moves.swap_remove(index).0)
+}
+
+/// Hands out pseudo-legal moves one at a time, best guesses first, so that a cutoff
+/// early in the list saves scoring and sorting the rest
+pub struct MovePicker {
+    stage: Stage,
+    quiescence: bool,
+    transposition_move: Move,
+    killers: [Move; 2],
+    killer_index: usize,
+    counter_move: Move,
+    captures: Vec<(Move, i32)>,
+    quiets: Vec<(Move, i32)>,
+    bad_captures: Vec<(Move, i32)>,
+    quiets_generated: bool,
+    quiets_scored: bool,
+}
+
+impl MovePicker {
+    /// The quiet moves are only generated once the captures have not given a cutoff, unless in check,
+    /// where all the evasions are generated at once.
+    pub fn new(board: &Board, in_check: bool, transposition_move: Move, killers: [Move; 2], counter_move: Move) -> Self {
+        let mut captures = Vec::new();
+        let mut quiets = Vec::new();
+        let mut bad_captures = Vec::new();
+
+        let moves = board.generate_moves_of_kind(if in_check { GenType::Evasions } else { GenType::Captures });
+        for mov in moves {
+            if mov.is_capture() || mov.promotion() == PieceType::Queen {
+                let score = mvv_lva(board, &mov);
+                captures.push((mov, score));
+            } else if mov.is_promotion() {
+                let score = mvv_lva(board, &mov);
+                bad_captures.push((mov, score));
+            } else {
+                quiets.push((mov, 0));
+            }
+        }
+
+        MovePicker {
+            stage: Stage::TranspositionMove,
+            quiescence: false,
+            transposition_move,
+            killers,
+            killer_index: 0,
+            counter_move,
+            captures,
+            quiets,
+            bad_captures,
+            quiets_generated: in_check,
+            quiets_scored: false,
+        }
+    }
+
+    fn generate_quiets(&mut self, board: &Board) {
+        if !self.quiets_generated {
+            self.quiets.extend(board.generate_moves_of_kind(GenType::Quiets).into_iter().map(|mov| (mov, 0)));
+            self.quiets_generated = true;
+        }
+    }
+
+    /// Only the captures that do not lose material, for the quiescence search, or every evasion when in check
+    pub fn new_quiescence(board: &Board, in_check: bool) -> Self {
+        let mut picker = MovePicker::new(board, in_check, EMPTY_MOVE, [EMPTY_MOVE, EMPTY_MOVE], EMPTY_MOVE);
+        picker.quiescence = !in_check;
+        picker.stage = Stage::GoodCaptures;
+        picker
+    }
+
+    /// The next move to search, None once every move has been handed out
+    pub fn next(&mut self, board: &Board, history: &HistoryTable) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::TranspositionMove => {
+                    self.stage = Stage::GoodCaptures;
+                    let mov = self.transposition_move;
+                    if mov == EMPTY_MOVE {
+                        continue;
+                    }
+                    if take(&mut self.captures, &mov) || take(&mut self.bad_captures, &mov) {
+                        return Some(mov);
+                    }
+                    self.generate_quiets(board);
+                    if take(&mut self.quiets, &mov) {
+                        return Some(mov);
+                    }
+                },
+                Stage::GoodCaptures => match take_best(&mut self.captures) {
+                    Some(mov) => {
+                        if see(board, &mov) < 0 {
+                            let score = mvv_lva(board, &mov);
+                            self.bad_captures.push((mov, score));
+                        } else {
+                            return Some(mov);
+                        }
+                    },
+                    None => {
+                        self.stage = if self.quiescence { Stage::Done } else { Stage::Killers };
+                    },
+                },
+                Stage::Killers => {
+                    self.generate_quiets(board);
+                    if self.killer_index == self.killers.len() {
+                        self.stage = Stage::CounterMove;
+                        continue;
+                    }
+                    let killer = self.killers[self.killer_index];
+                    self.killer_index += 1;
+                    if take(&mut self.quiets, &killer) {
+                        return Some(killer);
+                    }
+                },
+                Stage::CounterMove => {
+                    self.stage = Stage::Quiets;
+                    let mov = self.counter_move;
+                    if take(&mut self.quiets, &mov) {
+                        return Some(mov);
+                    }
+                },
+                Stage::Quiets => {
+                    if !self.quiets_scored {
+                        for (mov, score) in self.quiets.iter_mut() {
+                            *score = history[mov.start_square() as usize][mov.end_square() as usize];
+                        }
+                        self.quiets_scored = true;
+                    }
+                    match take_best(&mut self.quiets) {
+                        Some(mov) => return Some(mov),
+                        None => self.stage = Stage::BadCaptures,
+                    }
+                },
+                Stage::BadCaptures => match take_best(&mut self.bad_captures) {
+                    Some(mov) => return Some(mov),
+                    None => self.stage = Stage::Done,
+                },
+                Stage::Done => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,407 @@
+//! Optional NNUE evaluation, with accumulators updated incrementally as moves are made.
+use crate::board::Board;
+use crate::types::*;
+
+use std::fmt;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read};
+use std::sync::Arc;
+
+// File layout, all little endian:
+// magic, feature set (u32), hidden size (u32),
+// feature weights (i16, inputs * hidden), feature biases (i16, hidden),
+// output weights (i8, 2 * hidden, side to move first), output bias (i32)
+const MAGIC: &[u8; 8] = b"CHV3NNUE";
+
+// The accumulator is clipped to 0..=QA, output weights are scaled by QB
+const QA: i32 = 255;
+const QB: i32 = 64;
+const OUTPUT_SCALE: i32 = 400;
+
+// Accumulators are fixed-size arrays, so updating them never allocates. Larger networks are rejected
+const MAX_HIDDEN_SIZE: usize = 1024;
+// Enough for the deepest search line, the stack only grows past it when a game goes on with NNUE enabled
+const STACK_CAPACITY: usize = 256;
+
+//                  White, Black
+type Accumulator = [[i16; MAX_HIDDEN_SIZE]; 2];
+
+/// Which inputs a network has
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeatureSet {
+    /// King square x the other 10 pieces
+    HalfKP,
+    /// King square x all 12 pieces
+    HalfKA,
+}
+
+/// A network with one hidden layer, as loaded from a network file
+pub struct Network {
+    feature_set: FeatureSet,
+    hidden_size: usize,
+    feature_weights: Vec<i16>,
+    feature_biases: Vec<i16>,
+    output_weights: Vec<i8>,
+    output_bias: i32,
+}
+
+struct Reader {
+    bytes: Vec<u8>,
+    position: usize,
+}
+
+impl Reader {
+    fn take<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        if self.position + N > self.bytes.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "network file is truncated"));
+        }
+        let mut result = [0; N];
+        result.copy_from_slice(&self.bytes[self.position..self.position + N]);
+        self.position += N;
+        Ok(result)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    fn i16s(&mut self, count: usize) -> io::Result<Vec<i16>> {
+        (0..count).map(|_| Ok(i16::from_le_bytes(self.take()?))).collect()
+    }
+
+    fn i8s(&mut self, count: usize) -> io::Result<Vec<i8>> {
+        (0..count).map(|_| Ok(i8::from_le_bytes(self.take()?))).collect()
+    }
+}
+
+impl Network {
+    /// Reads a network file, failing on anything but the exact layout above
+    pub fn load(path: &str) -> io::Result<Network> {
+        let mut reader = Reader { bytes: Vec::new(), position: 0 };
+        File::open(path)?.read_to_end(&mut reader.bytes)?;
+
+        if &reader.take::<8>()? != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a network file"));
+        }
+        let feature_set = match reader.u32()? {
+            0 => FeatureSet::HalfKP,
+            1 => FeatureSet::HalfKA,
+            x => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown feature set {x}"))),
+        };
+        let hidden_size = reader.u32()? as usize;
+        if hidden_size == 0 || !hidden_size.is_multiple_of(16) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "hidden size must be a multiple of 16"));
+        }
+        if hidden_size > MAX_HIDDEN_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("hidden size is larger than {MAX_HIDDEN_SIZE}")));
+        }
+
+        let mut network = Network {
+            feature_set,
+            hidden_size,
+            feature_weights: Vec::new(),
+            feature_biases: Vec::new(),
+            output_weights: Vec::new(),
+            output_bias: 0,
+        };
+        network.feature_weights = reader.i16s(network.inputs() * hidden_size)?;
+        network.feature_biases = reader.i16s(hidden_size)?;
+        network.output_weights = reader.i8s(2 * hidden_size)?;
+        network.output_bias = reader.i32()?;
+
+        if reader.position != reader.bytes.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "network file has trailing data"));
+        }
+        Ok(network)
+    }
+
+    fn piece_kinds(&self) -> usize {
+        match self.feature_set {
+            FeatureSet::HalfKP => 10,
+            FeatureSet::HalfKA => 12,
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        64 * self.piece_kinds() * 64
+    }
+
+    // Squares are mirrored vertically for Black, so both sides see the board from their own side
+    fn feature_index(&self, perspective: Color, king_square: Square, piece: &Piece, square: Square) -> Option<usize> {
+        let typ = match piece.typ {
+            PieceType::Pawn   => 0,
+            PieceType::Rook   => 1,
+            PieceType::Knight => 2,
+            PieceType::Bishop => 3,
+            PieceType::Queen  => 4,
+            PieceType::King if self.feature_set == FeatureSet::HalfKA => 5,
+            _ => return None,
+        };
+        let kind = if piece.color == perspective {
+            typ
+        } else {
+            typ + self.piece_kinds() / 2
+        };
+        let (king_square, square) = if perspective == Color::White {
+            (king_square, square)
+        } else {
+            (king_square.flip(), square.flip())
+        };
+
+        Some((king_square as usize * self.piece_kinds() + kind) * 64 + square as usize)
+    }
+
+    fn add_feature(&self, values: &mut [i16], index: usize) {
+        let weights = &self.feature_weights[index * self.hidden_size..(index + 1) * self.hidden_size];
+        for (value, weight) in values.iter_mut().zip(weights) {
+            *value += weight;
+        }
+    }
+
+    fn remove_feature(&self, values: &mut [i16], index: usize) {
+        let weights = &self.feature_weights[index * self.hidden_size..(index + 1) * self.hidden_size];
+        for (value, weight) in values.iter_mut().zip(weights) {
+            *value -= weight;
+        }
+    }
+
+    fn refresh(&self, board: &Board, perspective: Color, values: &mut [i16]) {
+        values.copy_from_slice(&self.feature_biases);
+        let kings = board.kings & if perspective == Color::White { board.white_pieces } else { board.black_pieces };
+        if kings.is_empty() {
+            return;
+        }
+        let king_square = kings.lsb();
+
+        for square in board.white_pieces | board.black_pieces {
+            if let Some(index) = self.feature_index(perspective, king_square, &board.get_piece(square), square) {
+                self.add_feature(values, index);
+            }
+        }
+    }
+
+    fn output(&self, own: &[i16], other: &[i16]) -> i32 {
+        let (own_weights, other_weights) = self.output_weights.split_at(self.hidden_size);
+
+        #[cfg(target_arch = "x86_64")]
+        let sum = if is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just checked and the hidden size is a multiple of 16
+            unsafe { clipped_dot_avx2(own, own_weights) + clipped_dot_avx2(other, other_weights) }
+        } else {
+            clipped_dot(own, own_weights) + clipped_dot(other, other_weights)
+        };
+        #[cfg(not(target_arch = "x86_64"))]
+        let sum = clipped_dot(own, own_weights) + clipped_dot(other, other_weights);
+
+        // The sum reaches 2 x hidden size x QA x QB and the bias is read from the file, scaling either overflows i32
+        ((i64::from(sum) + i64::from(self.output_bias)) * i64::from(OUTPUT_SCALE) / i64::from(QA * QB)) as i32
+    }
+}
+
+fn clipped_dot(values: &[i16], weights: &[i8]) -> i32 {
+    values
+        .iter()
+        .zip(weights)
+        .map(|(value, weight)| (*value as i32).clamp(0, QA) * *weight as i32)
+        .sum()
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn clipped_dot_avx2(values: &[i16], weights: &[i8]) -> i32 {
+    use std::arch::x86_64::*;
+
+    let zero = _mm256_setzero_si256();
+    let max = _mm256_set1_epi16(QA as i16);
+    let mut sum = _mm256_setzero_si256();
+
+    for i in (0..values.len()).step_by(16) {
+        let value = _mm256_loadu_si256(values.as_ptr().add(i) as *const __m256i);
+        let clipped = _mm256_min_epi16(_mm256_max_epi16(value, zero), max);
+        let weight = _mm256_cvtepi8_epi16(_mm_loadu_si128(weights.as_ptr().add(i) as *const __m128i));
+        sum = _mm256_add_epi32(sum, _mm256_madd_epi16(clipped, weight));
+    }
+
+    let halves = _mm_add_epi32(_mm256_castsi256_si128(sum), _mm256_extracti128_si256::<1>(sum));
+    let pairs = _mm_add_epi32(halves, _mm_shuffle_epi32::<0b01001110>(halves));
+    let total = _mm_add_epi32(pairs, _mm_shuffle_epi32::<0b10110001>(pairs));
+    _mm_cvtsi128_si32(total)
+}
+
+/// The accumulators of each position in a board's move history, empty until NNUE is enabled
+// Accumulators are derived from the pieces on the board, so they take no part in comparing or hashing boards
+#[derive(Clone)]
+pub struct AccumulatorStack {
+    network: Option<Arc<Network>>,
+    // Entries above the current one are kept and overwritten, so making a move does not allocate
+    stack: Vec<Accumulator>,
+    current: usize,
+}
+
+impl AccumulatorStack {
+    /// A stack without a network, which ignores every move
+    pub const fn new() -> Self {
+        AccumulatorStack {
+            network: None,
+            stack: Vec::new(),
+            current: 0,
+        }
+    }
+
+    // Copies the current accumulators one entry up and returns both, for the next move to update
+    fn push(&mut self) -> (&Accumulator, &mut Accumulator) {
+        if self.current + 1 == self.stack.len() {
+            self.stack.push([[0; MAX_HIDDEN_SIZE]; 2]);
+        }
+        self.current += 1;
+        let (below, above) = self.stack.split_at_mut(self.current);
+        (&below[self.current - 1], &mut above[0])
+    }
+
+    /// Repeats the current accumulators for a null move, as nothing moves in one
+    pub fn duplicate(&mut self) {
+        if self.network.is_some() {
+            let (previous, next) = self.push();
+            *next = *previous;
+        }
+    }
+
+    /// Goes back to the accumulators from before the last move
+    pub fn pop(&mut self) {
+        if self.network.is_some() {
+            self.current -= 1;
+        }
+    }
+}
+
+impl Default for AccumulatorStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Any two stacks are equal, so boards compare as equal whether or not NNUE is enabled on them. The accumulators
+/// follow from the pieces on the board, which Board compares already
+impl PartialEq for AccumulatorStack {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for AccumulatorStack {}
+
+/// Hashes nothing, to agree with PartialEq
+impl Hash for AccumulatorStack {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl fmt::Debug for AccumulatorStack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let entries = if self.network.is_some() { self.current + 1 } else { 0 };
+        write!(f, "AccumulatorStack({entries} entries)")
+    }
+}
+
+impl Board {
+    /// Computes the accumulators of the current position, from then on they follow every move made
+    pub fn enable_nnue(&mut self, network: Arc<Network>) {
+        let hidden_size = network.hidden_size;
+        let mut stack = Vec::with_capacity(STACK_CAPACITY);
+        stack.push([[0; MAX_HIDDEN_SIZE]; 2]);
+        network.refresh(self, Color::White, &mut stack[0][0][..hidden_size]);
+        network.refresh(self, Color::Black, &mut stack[0][1][..hidden_size]);
+        self.accumulators = AccumulatorStack {
+            network: Some(network),
+            stack,
+            current: 0,
+        };
+    }
+
+    /// Called at the end of make_move, after the move has been played on the bitboards
+    pub fn update_accumulators(&mut self, mov: &Move, start_piece: &Piece, captured: &Piece, en_passant_capture: &Option<(Square, Piece)>) {
+        if self.accumulators.network.is_none() {
+            return;
+        }
+
+        let (start_square, end_square) = (mov.start_square(), mov.end_square());
+        let color = start_piece.color;
+        let moved_type = if mov.is_promotion() { mov.promotion() } else { start_piece.typ };
+
+        // A move takes at most two pieces off squares and puts at most two on squares
+        let mut removed = [(start_piece.typ, color, start_square); 2];
+        let mut added = [(moved_type, color, end_square); 2];
+        let (mut removed_count, mut added_count) = (1, 1);
+        if captured.typ != PieceType::Empty {
+            removed[1] = (captured.typ, captured.color, end_square);
+            removed_count = 2;
+        }
+        if let Some((square, pawn)) = en_passant_capture {
+            removed[1] = (pawn.typ, pawn.color, *square);
+            removed_count = 2;
+        }
+        if mov.is_castle() {
+            let (rook_start, rook_end) = match end_square {
+                Square::C1 => (Square::A1, Square::D1),
+                Square::G1 => (Square::H1, Square::F1),
+                Square::G8 => (Square::H8, Square::F8),
+                _          => (Square::A8, Square::D8),
+            };
+            removed[1] = (PieceType::Rook, color, rook_start);
+            added[1] = (PieceType::Rook, color, rook_end);
+            (removed_count, added_count) = (2, 2);
+        }
+
+        // Taken out of the board for the update, so the board can be read while the stack is written
+        let mut accumulators = std::mem::take(&mut self.accumulators);
+        let network = accumulators.network.take().unwrap();
+        let hidden_size = network.hidden_size;
+        let (previous, next) = accumulators.push();
+
+        for (side, perspective) in [Color::White, Color::Black].into_iter().enumerate() {
+            let values = &mut next[side][..hidden_size];
+            if start_piece.typ == PieceType::King && color == perspective {
+                network.refresh(self, perspective, values);
+                continue;
+            }
+            values.copy_from_slice(&previous[side][..hidden_size]);
+
+            let kings = self.kings & if perspective == Color::White { self.white_pieces } else { self.black_pieces };
+            if kings.is_empty() {
+                continue;
+            }
+            let king_square = kings.lsb();
+
+            for &(typ, piece_color, square) in &removed[..removed_count] {
+                if let Some(index) = network.feature_index(perspective, king_square, &Piece { typ, color: piece_color }, square) {
+                    network.remove_feature(values, index);
+                }
+            }
+            for &(typ, piece_color, square) in &added[..added_count] {
+                if let Some(index) = network.feature_index(perspective, king_square, &Piece { typ, color: piece_color }, square) {
+                    network.add_feature(values, index);
+                }
+            }
+        }
+
+        accumulators.network = Some(network);
+        self.accumulators = accumulators;
+    }
+}
+
+/// The network's score of the position in centipawns, seen from White like the handcrafted evaluation
+pub fn evaluate(board: &Board) -> i32 {
+    let network = board.accumulators.network.as_ref().expect("NNUE evaluation without a network");
+    let [white, black] = &board.accumulators.stack[board.accumulators.current];
+    let (white, black) = (&white[..network.hidden_size], &black[..network.hidden_size]);
+
+    if board.turn == Color::White {
+        network.output(white, black)
+    } else {
+        -network.output(black, white)
+    }
+}
@@ -0,0 +1,160 @@
+//! The engine settings that the UCI options change, shared by every search.
+use crate::transposition_table::{TranspositionTable, DEFAULT_HASH_MEGABYTES};
+use crate::perft::{PerftTable, DEFAULT_PERFT_HASH_MEGABYTES};
+#[cfg(feature = "nnue")]
+use crate::nnue::Network;
+
+use std::sync::Arc;
+
+const MAX_HASH_MEGABYTES: usize = 65_536;
+const MAX_THREADS: usize = 256;
+const MAX_MULTI_PV: usize = 256;
+
+/// The engine settings, set through the UCI options of the same names.
+#[derive(Clone)]
+pub struct SearchOptions {
+    /// Shared by every search thread and kept between searches.
+    pub transposition_table: Arc<TranspositionTable>,
+    /// The size of the transposition table.
+    pub hash_megabytes: usize,
+    /// The number of search threads, and of perft threads.
+    pub threads: usize,
+    /// The table "go perft" keeps its counts in between commands, None to count every node.
+    pub perft_table: Option<Arc<PerftTable>>,
+    /// The size of the perft table, 0 for none.
+    pub perft_hash_megabytes: usize,
+    /// The number of best lines searched and reported.
+    pub multi_pv: usize,
+    /// One thread and a cleared transposition table, and no time limits on a search with a depth or node limit,
+    /// so equal searches give equal results. Searches without one still keep to the clock.
+    pub deterministic: bool,
+    /// Without it every move is searched with a full window, only useful for testing.
+    pub alpha_beta: bool,
+    /// Whether null move pruning is done.
+    pub null_move_pruning: bool,
+    /// Whether late quiet moves are searched at a reduced depth first.
+    pub late_move_reductions: bool,
+    /// Whether reverse futility pruning is done.
+    pub reverse_futility_pruning: bool,
+    /// Whether futility pruning is done.
+    pub futility_pruning: bool,
+    /// Whether late move pruning is done.
+    pub late_move_pruning: bool,
+    /// Whether moves that give check are extended.
+    pub check_extension: bool,
+    /// Whether a transposition table move that is much better than the others is extended.
+    pub singular_extension: bool,
+    /// Whether recaptures on the square of the last capture are extended.
+    pub recapture_extension: bool,
+    /// Whether pawn pushes to the seventh rank are extended.
+    pub passed_pawn_extension: bool,
+    /// Whether the network evaluates positions instead of the handcrafted evaluation.
+    #[cfg(feature = "nnue")]
+    pub use_nnue: bool,
+    /// The network loaded from EvalFile, if any.
+    #[cfg(feature = "nnue")]
+    pub network: Option<Arc<Network>>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            transposition_table: Arc::new(TranspositionTable::new(DEFAULT_HASH_MEGABYTES)),
+            hash_megabytes: DEFAULT_HASH_MEGABYTES,
+            threads: 1,
+            perft_table: Some(Arc::new(PerftTable::new(DEFAULT_PERFT_HASH_MEGABYTES))),
+            perft_hash_megabytes: DEFAULT_PERFT_HASH_MEGABYTES,
+            multi_pv: 1,
+            deterministic: false,
+            alpha_beta: true,
+            null_move_pruning: true,
+            late_move_reductions: true,
+            reverse_futility_pruning: true,
+            futility_pruning: true,
+            late_move_pruning: true,
+            check_extension: true,
+            singular_extension: true,
+            recapture_extension: false,
+            passed_pawn_extension: false,
+            #[cfg(feature = "nnue")]
+            use_nnue: false,
+            #[cfg(feature = "nnue")]
+            network: None,
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Prints the option lines of the reply to "uci", with the current values as defaults.
+    pub fn print_uci_options(&self) {
+        println!("option name Hash type spin default {} min 1 max {}", self.hash_megabytes, MAX_HASH_MEGABYTES);
+        println!("option name Threads type spin default {} min 1 max {}", self.threads, MAX_THREADS);
+        println!("option name PerftHash type spin default {} min 0 max {}", self.perft_hash_megabytes, MAX_HASH_MEGABYTES);
+        println!("option name MultiPV type spin default {} min 1 max {}", self.multi_pv, MAX_MULTI_PV);
+        println!("option name Deterministic type check default {}", self.deterministic);
+        println!("option name NullMovePruning type check default {}", self.null_move_pruning);
+        println!("option name LateMoveReductions type check default {}", self.late_move_reductions);
+        println!("option name ReverseFutilityPruning type check default {}", self.reverse_futility_pruning);
+        println!("option name FutilityPruning type check default {}", self.futility_pruning);
+        println!("option name LateMovePruning type check default {}", self.late_move_pruning);
+        println!("option name CheckExtension type check default {}", self.check_extension);
+        println!("option name SingularExtension type check default {}", self.singular_extension);
+        println!("option name RecaptureExtension type check default {}", self.recapture_extension);
+        println!("option name PassedPawnExtension type check default {}", self.passed_pawn_extension);
+        #[cfg(feature = "nnue")]
+        {
+            println!("option name UseNNUE type check default {}", self.use_nnue);
+            println!("option name EvalFile type string default <empty>");
+        }
+    }
+
+    /// Expects the arguments of "setoption", i.e. `name <id> [value <x>]`
+    pub fn set_option(&mut self, arguments: &[&str]) {
+        let value_position = arguments.iter().position(|argument| *argument == "value").unwrap_or(arguments.len());
+        let name = arguments[1.min(value_position)..value_position].join(" ");
+        let value = arguments[usize::min(value_position + 1, arguments.len())..].join(" ");
+
+        match (name.as_str(), value.as_str()) {
+            ("Hash", value) => match value.parse::<usize>() {
+                Ok(megabytes) => {
+                    self.hash_megabytes = megabytes.clamp(1, MAX_HASH_MEGABYTES);
+                    self.transposition_table = Arc::new(TranspositionTable::new(self.hash_megabytes));
+                },
+                Err(_) => println!("info string Invalid Hash value: {value}"),
+            },
+            ("Threads", value) => match value.parse::<usize>() {
+                Ok(threads) => self.threads = threads.clamp(1, MAX_THREADS),
+                Err(_) => println!("info string Invalid Threads value: {value}"),
+            },
+            ("PerftHash", value) => match value.parse::<usize>() {
+                Ok(megabytes) => {
+                    self.perft_hash_megabytes = megabytes.min(MAX_HASH_MEGABYTES);
+                    self.perft_table = (megabytes > 0).then(|| Arc::new(PerftTable::new(self.perft_hash_megabytes)));
+                },
+                Err(_) => println!("info string Invalid PerftHash value: {value}"),
+            },
+            ("MultiPV", value) => match value.parse::<usize>() {
+                Ok(multi_pv) => self.multi_pv = multi_pv.clamp(1, MAX_MULTI_PV),
+                Err(_) => println!("info string Invalid MultiPV value: {value}"),
+            },
+            ("Deterministic", value) => self.deterministic = value == "true",
+            ("NullMovePruning", value) => self.null_move_pruning = value == "true",
+            ("LateMoveReductions", value) => self.late_move_reductions = value == "true",
+            ("ReverseFutilityPruning", value) => self.reverse_futility_pruning = value == "true",
+            ("FutilityPruning", value) => self.futility_pruning = value == "true",
+            ("LateMovePruning", value) => self.late_move_pruning = value == "true",
+            ("CheckExtension", value) => self.check_extension = value == "true",
+            ("SingularExtension", value) => self.singular_extension = value == "true",
+            ("RecaptureExtension", value) => self.recapture_extension = value == "true",
+            ("PassedPawnExtension", value) => self.passed_pawn_extension = value == "true",
+            #[cfg(feature = "nnue")]
+            ("UseNNUE", value) => self.use_nnue = value == "true",
+            #[cfg(feature = "nnue")]
+            ("EvalFile", value) => match Network::load(value) {
+                Ok(network) => self.network = Some(Arc::new(network)),
+                Err(error) => println!("info string Could not load {value}: {error}"),
+            },
+            (name, _) => println!("info string Unknown option: {name}"),
+        }
+    }
+}
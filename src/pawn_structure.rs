@@ -0,0 +1,171 @@
+//! Pawn structure and passed pawn evaluation, cached in a pawn hash table.
+use crate::board::Board;
+use crate::types::*;
+use crate::evaluation::*;
+use crate::move_generator::pawn_attacks;
+
+const PAWN_HASH_TABLE_SIZE: usize = 1 << 14;
+
+// Indexed by the rank of the pawn as seen from its own side
+const PASSED_PAWN: [Score; 8] = [
+    Score::new(0, 0), Score::new(5, 10), Score::new(10, 15), Score::new(15, 25),
+    Score::new(25, 45), Score::new(45, 75), Score::new(70, 120), Score::new(0, 0),
+];
+const PASSED_PAWN_FREE_PATH: [Score; 8] = [
+    Score::new(0, 0), Score::new(0, 0), Score::new(0, 5), Score::new(0, 10),
+    Score::new(0, 20), Score::new(0, 35), Score::new(0, 60), Score::new(0, 0),
+];
+const PASSED_PAWN_KING_DISTANCE: [i32; 8] = [0, 0, 0, 1, 2, 3, 4, 0];
+const CONNECTED_PAWN: [Score; 8] = [
+    Score::new(0, 0), Score::new(3, 2), Score::new(5, 4), Score::new(8, 6),
+    Score::new(15, 12), Score::new(25, 20), Score::new(40, 30), Score::new(0, 0),
+];
+const PHALANX_PAWN: [Score; 8] = [
+    Score::new(0, 0), Score::new(2, 1), Score::new(4, 2), Score::new(6, 4),
+    Score::new(12, 8), Score::new(20, 15), Score::new(30, 25), Score::new(0, 0),
+];
+const CANDIDATE_PASSED_PAWN: [Score; 8] = [
+    Score::new(0, 0), Score::new(2, 4), Score::new(4, 8), Score::new(7, 14),
+    Score::new(12, 24), Score::new(20, 40), Score::new(0, 0), Score::new(0, 0),
+];
+const ISOLATED_PAWN: Score = Score::new(-10, -15);
+const DOUBLED_PAWN: Score = Score::new(-10, -20);
+const BACKWARD_PAWN: Score = Score::new(-8, -10);
+
+#[derive(Clone, Copy, Default)]
+struct PawnHashEntry {
+    key: u64,
+    score: Score,
+    //            White, Black
+    passed_pawns: (Bitboard, Bitboard),
+}
+
+/// Pawn structure scores and passed pawns by pawn key, as they only change when a pawn moves or is taken
+pub struct PawnHashTable {
+    entries: Vec<PawnHashEntry>,
+}
+
+impl PawnHashTable {
+    /// An empty table
+    pub fn new() -> Self {
+        PawnHashTable {
+            entries: vec![PawnHashEntry::default(); PAWN_HASH_TABLE_SIZE],
+        }
+    }
+
+    fn probe(&mut self, board: &Board) -> PawnHashEntry {
+        let key = board.pawn_key();
+        let index = key as usize & (PAWN_HASH_TABLE_SIZE - 1);
+        if self.entries[index].key != key {
+            let (white_score, white_passed) = evaluate_pawn_side(board, Color::White);
+            let (black_score, black_passed) = evaluate_pawn_side(board, Color::Black);
+            self.entries[index] = PawnHashEntry {
+                key,
+                score: white_score - black_score,
+                passed_pawns: (white_passed, black_passed),
+            };
+        }
+        self.entries[index]
+    }
+}
+
+impl Default for PawnHashTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn stop_square(square: Square, color: Color) -> Square {
+    if color == Color::White {
+        square.offset(8)
+    } else {
+        square.offset(-8)
+    }
+}
+
+/// The pawn structure score of the color and its passed pawns
+pub fn evaluate_pawn_side(board: &Board, color: Color) -> (Score, Bitboard) {
+    let (own_pieces, enemy_pieces) = side_bitboards(board, color);
+    let enemy_color = if color == Color::White { Color::Black } else { Color::White };
+    let own_pawns = board.pawns & own_pieces;
+    let enemy_pawns = board.pawns & enemy_pieces;
+    let own_pawn_attacks = pawn_attacks(own_pawns, color);
+    let enemy_pawn_attacks = pawn_attacks(enemy_pawns, enemy_color);
+
+    let mut score = Score::default();
+    let mut passed_pawns = Bitboard::EMPTY;
+    for square in own_pawns {
+        let rank = relative_rank(square, color);
+        let front = front_span(square, color);
+        let adjacent_front = neighbours(front);
+        let adjacent_files = neighbours(square.file().bitboard());
+        let adjacent_behind = adjacent_files & !adjacent_front;
+
+        let doubled = !(own_pawns & front).is_empty();
+        let isolated = (own_pawns & adjacent_files).is_empty();
+        let supported = own_pawn_attacks.contains(square);
+        let phalanx = !(own_pawns & neighbours(square.bitboard())).is_empty();
+        let passed = !doubled && (enemy_pawns & (front | adjacent_front)).is_empty();
+
+        if doubled {
+            score += DOUBLED_PAWN;
+        }
+
+        if isolated {
+            score += ISOLATED_PAWN;
+        } else if (own_pawns & adjacent_behind).is_empty()
+            && enemy_pawn_attacks.contains(stop_square(square, color)) {
+            score += BACKWARD_PAWN;
+        }
+
+        if supported {
+            score += CONNECTED_PAWN[rank];
+        }
+        if phalanx {
+            score += PHALANX_PAWN[rank];
+        }
+
+        if passed {
+            passed_pawns |= square.bitboard();
+        } else if !doubled && (enemy_pawns & front).is_empty()
+            && (own_pawns & adjacent_behind).count() >= (enemy_pawns & adjacent_front).count() {
+            score += CANDIDATE_PASSED_PAWN[rank];
+        }
+    }
+
+    (score, passed_pawns)
+}
+
+/// Depends on the position of the other pieces, so it can not be stored in the pawn hash table
+pub fn evaluate_passed_pawns(board: &Board, color: Color, passed_pawns: Bitboard) -> Score {
+    let (own_pieces, enemy_pieces) = side_bitboards(board, color);
+    let occupied = own_pieces | enemy_pieces;
+    let own_king = (board.kings & own_pieces).lsb();
+    let enemy_king = (board.kings & enemy_pieces).lsb();
+
+    let mut score = Score::default();
+
+    for square in passed_pawns {
+        let rank = relative_rank(square, color);
+        let stop = stop_square(square, color);
+
+        score += PASSED_PAWN[rank];
+        if (occupied & front_span(square, color)).is_empty() {
+            score += PASSED_PAWN_FREE_PATH[rank];
+        }
+
+        let king_distance = distance(enemy_king, stop) * 5 - distance(own_king, stop) * 2;
+        score += Score::new(0, king_distance * PASSED_PAWN_KING_DISTANCE[rank]);
+    }
+
+    score
+}
+
+/// The pawn structure and passed pawn score of the position, seen from White
+pub fn evaluate_pawns(board: &Board, pawn_hash_table: &mut PawnHashTable) -> Score {
+    let entry = pawn_hash_table.probe(board);
+
+    entry.score
+        + evaluate_passed_pawns(board, Color::White, entry.passed_pawns.0)
+        - evaluate_passed_pawns(board, Color::Black, entry.passed_pawns.1)
+}
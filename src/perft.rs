@@ -0,0 +1,132 @@
+//! Perft, counting the leaf nodes of the move tree to test move generation.
+use crate::board::Board;
+use crate::types::*;
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// The size of the perft table until the PerftHash option changes it
+pub const DEFAULT_PERFT_HASH_MEGABYTES: usize = 16;
+
+// Mixed into the key, so that the same position at different depths gets different entries
+const DEPTH_KEY: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// Lock-free like the transposition table, the key is stored xored with the count
+struct Entry {
+    key: AtomicU64,
+    count: AtomicU64,
+}
+
+/// Perft counts by Zobrist key and depth.
+pub struct PerftTable {
+    entries: Vec<Entry>,
+}
+
+impl PerftTable {
+    /// An empty table taking up about the given number of megabytes.
+    pub fn new(megabytes: usize) -> Self {
+        let size = usize::max(megabytes * 1024 * 1024 / std::mem::size_of::<Entry>(), 1);
+        PerftTable {
+            entries: (0..size).map(|_| Entry { key: AtomicU64::new(0), count: AtomicU64::new(0) }).collect(),
+        }
+    }
+
+    fn key(board: &Board, depth: i32) -> u64 {
+        board.zobrist_key() ^ (depth as u64).wrapping_mul(DEPTH_KEY)
+    }
+
+    fn entry(&self, key: u64) -> &Entry {
+        &self.entries[(key % self.entries.len() as u64) as usize]
+    }
+
+    fn probe(&self, key: u64) -> Option<u64> {
+        let entry = self.entry(key);
+        let count = entry.count.load(Ordering::Relaxed);
+        if count != 0 && entry.key.load(Ordering::Relaxed) ^ count == key {
+            Some(count)
+        } else {
+            None
+        }
+    }
+
+    fn store(&self, key: u64, count: u64) {
+        let entry = self.entry(key);
+        entry.key.store(key ^ count, Ordering::Relaxed);
+        entry.count.store(count, Ordering::Relaxed);
+    }
+}
+
+/// The number of leaf nodes depth plies below the position
+pub fn perft_nodes(depth: i32, board: &mut Board, table: Option<&PerftTable>) -> u64 {
+    if depth <= 0 {
+        return 1;
+    }
+
+    let moves = board.generate_legal_moves();
+    // Bulk counting: the leaves below the last ply are just the legal moves
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let key = table.map(|_| PerftTable::key(board, depth));
+    if let Some(count) = table.zip(key).and_then(|(table, key)| table.probe(key)) {
+        return count;
+    }
+
+    let mut count = 0;
+    for mov in moves {
+        board.make_move(mov);
+        count += perft_nodes(depth - 1, board, table);
+        board.unmake_move(mov);
+    }
+
+    if let Some((table, key)) = table.zip(key) {
+        table.store(key, count);
+    }
+    count
+}
+
+/// Returns the count below every root move, in move generation order. The root moves are shared out
+/// between the threads, which take the next one whenever they are done with the previous
+pub fn divide(depth: i32, board: &Board, threads: usize, table: Option<&PerftTable>) -> Vec<(Move, u64)> {
+    let moves = board.clone().generate_legal_moves();
+    let counts = moves.iter().map(|_| AtomicU64::new(0)).collect::<Vec<AtomicU64>>();
+    let next = AtomicUsize::new(0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, moves.len().max(1)) {
+            scope.spawn(|| {
+                let mut board = board.clone();
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(mov) = moves.get(index) else {
+                        break;
+                    };
+                    board.make_move(*mov);
+                    counts[index].store(perft_nodes(depth - 1, &mut board, table), Ordering::Relaxed);
+                    board.unmake_move(*mov);
+                }
+            });
+        }
+    });
+
+    moves.into_iter().zip(counts.iter().map(|count| count.load(Ordering::Relaxed))).collect()
+}
+
+/// Prints the count below every root move and the total, the way other engines do, so the output can be diffed.
+/// The table may be kept between calls, its entries stay valid for any position
+pub fn perft(depth: i32, board: &Board, threads: usize, table: Option<&PerftTable>) -> u64 {
+    if depth <= 0 {
+        println!();
+        println!("Nodes searched: 1");
+        return 1;
+    }
+
+    let counts = divide(depth, board, threads, table);
+    for (mov, count) in &counts {
+        println!("{mov}: {count}");
+    }
+    let total = counts.iter().map(|(_, count)| count).sum();
+    println!();
+    println!("Nodes searched: {total}");
+    total
+}
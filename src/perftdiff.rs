@@ -0,0 +1,140 @@
+//! The perftdiff subcommand, comparing perft divides with a reference UCI engine to find move generation bugs.
+use crate::consts::*;
+use crate::types::*;
+use crate::perft;
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// A reference engine speaking UCI, which has to print divide output for "go perft"
+struct Engine {
+    process: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+}
+
+impl Engine {
+    fn start(path: &str) -> std::io::Result<Self> {
+        let mut process = Command::new(path).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let input = process.stdin.take().unwrap();
+        let output = BufReader::new(process.stdout.take().unwrap());
+        let mut engine = Engine { process, input, output };
+        engine.send("uci")?;
+        engine.read_until("uciok")?;
+        Ok(engine)
+    }
+
+    fn send(&mut self, command: &str) -> std::io::Result<()> {
+        writeln!(self.input, "{command}")?;
+        self.input.flush()
+    }
+
+    // Returns the lines up to and including the first that starts with prefix
+    fn read_until(&mut self, prefix: &str) -> std::io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            if self.output.read_line(&mut line)? == 0 {
+                return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, format!("The engine quit before sending {prefix}")));
+            }
+            let line = line.trim().to_string();
+            let done = line.starts_with(prefix);
+            lines.push(line);
+            if done {
+                return Ok(lines);
+            }
+        }
+    }
+
+    fn divide(&mut self, fen: &str, moves: &[Move], depth: i32) -> std::io::Result<HashMap<String, u64>> {
+        let mut position = format!("position fen {fen}");
+        if !moves.is_empty() {
+            position += &format!(" moves {}", moves.iter().map(Move::to_string).collect::<Vec<String>>().join(" "));
+        }
+        self.send(&position)?;
+        self.send(&format!("go perft {depth}"))?;
+
+        // Lines like "e2e4: 20", anything else the engine prints is skipped. Only the notation is checked,
+        // the move may well be one that chess_v3 does not generate
+        Ok(self.read_until("Nodes searched")?
+            .iter()
+            .filter_map(|line| line.split_once(": "))
+            .filter(|(mov, _)| EMPTY_BOARD.string_to_move(mov).is_some())
+            .filter_map(|(mov, count)| Some((mov.to_string(), count.trim().parse().ok()?)))
+            .collect())
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.process.wait();
+    }
+}
+
+fn print_moves(moves: &[String]) -> String {
+    if moves.is_empty() { "-".to_string() } else { moves.join(" ") }
+}
+
+/// Usage: `perftdiff <engine> <depth> [fen]`
+/// Compares the divide counts with the reference engine and follows the first move whose count differs,
+/// one ply deeper each time, until the move lists themselves differ
+pub fn perftdiff(arguments: &[String]) {
+    if arguments.len() < 2 {
+        println!("Usage: perftdiff <engine> <depth> [fen]");
+        return;
+    }
+    let Ok(depth) = arguments[1].parse::<i32>() else {
+        println!("Invalid depth: {}", arguments[1]);
+        return;
+    };
+    let fen = arguments.get(2).map(|fen| fen.as_str()).unwrap_or(START_FEN);
+
+    let mut engine = match Engine::start(&arguments[0]) {
+        Ok(engine) => engine,
+        Err(error) => {
+            println!("Could not start {}: {error}", arguments[0]);
+            return;
+        },
+    };
+    let mut board = EMPTY_BOARD;
+    board.load_fen(fen.to_string());
+    let mut moves: Vec<Move> = Vec::new();
+
+    for depth in (1..=depth).rev() {
+        let ours = perft::divide(depth, &board, 1, None);
+        let theirs = match engine.divide(fen, &moves, depth) {
+            Ok(theirs) => theirs,
+            Err(error) => {
+                println!("Could not read the reference divide: {error}");
+                return;
+            },
+        };
+
+        let ours_by_name = ours.iter().map(|(mov, count)| (mov.to_string(), *count)).collect::<HashMap<String, u64>>();
+        let mut missing = theirs.keys().filter(|mov| !ours_by_name.contains_key(*mov)).cloned().collect::<Vec<String>>();
+        let extra = ours.iter().map(|(mov, _)| mov.to_string()).filter(|mov| !theirs.contains_key(mov)).collect::<Vec<String>>();
+        missing.sort();
+
+        if !missing.is_empty() || !extra.is_empty() {
+            println!("Move lists differ after: {}", print_moves(&moves.iter().map(Move::to_string).collect::<Vec<String>>()));
+            println!("FEN: {}", board.to_fen());
+            println!("Only the reference engine generates: {}", print_moves(&missing));
+            println!("Only chess_v3 generates: {}", print_moves(&extra));
+            return;
+        }
+
+        let Some((mov, count)) = ours.into_iter().find(|(mov, count)| theirs[&mov.to_string()] != *count) else {
+            println!("No difference at depth {depth}");
+            return;
+        };
+        println!("{}: {} here, {} in the reference engine", mov, count, theirs[&mov.to_string()]);
+        board.make_move(mov);
+        moves.push(mov);
+    }
+    // Every count at depth 1 is one, so the move lists must have differed on the way down
+    println!("The counts differ, but no move list does");
+}
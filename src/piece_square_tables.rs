@@ -1,3 +1,5 @@
+//! Piece-square tables of the evaluation, as written by the tune subcommand.
+
 pub const WHITE_PAWN: [i32; 64] = [
     0,  0,  0,  0,  0,  0,  0,  0,
     5, 10, 10,-20,-20, 10, 10,  5,
@@ -21,14 +23,14 @@ pub const BLACK_PAWN: [i32; 64] = [
 ];
 
 pub const WHITE_ROOK: [i32; 64] = [
-    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  5,  5,  0,  0,  0,
     -5,  0,  0,  0,  0,  0,  0, -5,
     -5,  0,  0,  0,  0,  0,  0, -5,
     -5,  0,  0,  0,  0,  0,  0, -5,
     -5,  0,  0,  0,  0,  0,  0, -5,
     -5,  0,  0,  0,  0,  0,  0, -5,
     5, 10, 10, 10, 10, 10, 10,  5,
-    0,  0,  0,  5,  5,  0,  0,  0
+    0,  0,  0,  0,  0,  0,  0,  0
 ];
 
 pub const BLACK_ROOK: [i32; 64] = [
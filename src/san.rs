@@ -0,0 +1,74 @@
+//! Writing moves in standard algebraic notation.
+
+use crate::board::Board;
+use crate::types::*;
+
+fn piece_letter(typ: PieceType) -> &'static str {
+    match typ {
+        PieceType::Rook   => "R",
+        PieceType::Knight => "N",
+        PieceType::Bishop => "B",
+        PieceType::Queen  => "Q",
+        PieceType::King   => "K",
+        _ => "",
+    }
+}
+
+impl Board {
+    /// Standard algebraic notation of a legal move in this position, e.g. `Nbd2`, `exd6`, `e8=Q+` or `O-O-O`.
+    pub fn to_san(&mut self, mov: &Move) -> String {
+        let typ = self.get_piece(mov.start_square()).typ;
+        let mut san = if mov.is_castle() {
+            if mov.end_square() > mov.start_square() { "O-O".to_string() } else { "O-O-O".to_string() }
+        } else {
+            let mut san = piece_letter(typ).to_string();
+
+            if typ == PieceType::Pawn {
+                if mov.is_capture() {
+                    san += &mov.start_square().to_string()[0..1];
+                }
+            } else {
+                // The file, else the rank, else both tell the move apart from other pieces of the type reaching the square
+                let others = self.generate_legal_moves().into_iter().filter(|other| {
+                    other.end_square() == mov.end_square()
+                        && other.start_square() != mov.start_square()
+                        && self.get_piece(other.start_square()).typ == typ
+                }).collect::<Vec<Move>>();
+                let start = mov.start_square().to_string();
+                if !others.is_empty() {
+                    if others.iter().all(|other| other.start_square().file() != mov.start_square().file()) {
+                        san += &start[0..1];
+                    } else if others.iter().all(|other| other.start_square().rank() != mov.start_square().rank()) {
+                        san += &start[1..2];
+                    } else {
+                        san += &start;
+                    }
+                }
+            }
+
+            if mov.is_capture() {
+                san += "x";
+            }
+            san += &mov.end_square().to_string();
+            if mov.is_promotion() {
+                san += "=";
+                san += piece_letter(mov.promotion());
+            }
+            san
+        };
+
+        self.make_move(*mov);
+        if self.in_check() {
+            san += if self.generate_legal_moves().is_empty() { "#" } else { "+" };
+        }
+        self.unmake_move(*mov);
+        san
+    }
+
+    /// The legal move written as `san` in standard algebraic notation, check marks and annotations are optional.
+    pub fn from_san(&mut self, san: &str) -> Option<Move> {
+        let strip = |san: &str| san.trim().trim_end_matches(['+', '#', '!', '?']).replace('0', "O");
+        let san = strip(san);
+        self.generate_legal_moves().into_iter().find(|mov| strip(&self.to_san(mov)) == san)
+    }
+}
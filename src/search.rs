@@ -1,302 +1,944 @@
+//! The alpha-beta search: iterative deepening with aspiration windows, pruning, extensions,
+//! quiescence search and Lazy SMP threads sharing one transposition table.
 use crate::board::*;
 use crate::types::*;
 use crate::consts::*;
-use crate::piece_square_tables;
+use crate::evaluation::{evaluate, relative_rank};
+use crate::pawn_structure::PawnHashTable;
+use crate::options::SearchOptions;
+use crate::limits::SearchLimits;
+use crate::transposition_table::TranspositionTable;
+use crate::move_ordering::{MovePicker, HistoryTable, MAX_HISTORY};
+#[cfg(feature = "nnue")]
+use crate::nnue;
 
-use std::cmp::{min, max};
-
-use stoppable_thread;
-use tokio;
+use std::cmp::{min, max, Reverse};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
 
 type Stop = stoppable_thread::SimpleAtomicBool;
 
-pub fn evaluate(board: &mut Board) -> i32 {
-    let mut result = 0;
-    let mut queens = 0;
-    let mut minors = 0;
-
-    for square in 0..64 {
-        let piece = board.get_piece(square);
-
-        result += match piece.typ {
-            PieceType::Pawn  => 100 + match piece.color {
-                Color::White => piece_square_tables::WHITE_PAWN[square],
-                Color::Black => piece_square_tables::BLACK_PAWN[square],
-                Color::Empty => 0,
-            },
-            PieceType::Rook  => 500 + match piece.color {
-                Color::White => piece_square_tables::WHITE_ROOK[square],
-                Color::Black => piece_square_tables::BLACK_ROOK[square],
-                Color::Empty => 0,
-            },
-            PieceType::Knight => 320 + match piece.color {
-                Color::White => piece_square_tables::WHITE_KNIGHT[square],
-                Color::Black => piece_square_tables::BLACK_KNIGHT[square],
-                Color::Empty => 0,
-            },
-            PieceType::Bishop => 330 + match piece.color {
-                Color::White => piece_square_tables::WHITE_BISHOP[square],
-                Color::Black => piece_square_tables::BLACK_BISHOP[square],
-                Color::Empty => 0,
-            },
-            PieceType::Queen  => 900 + match piece.color {
-                Color::White => piece_square_tables::WHITE_QUEEN[square],
-                Color::Black => piece_square_tables::BLACK_QUEEN[square],
-                Color::Empty => 0,
-            },
-            _  => 0,
-        } * match piece.color {
-            Color::White => 1,
-            Color::Black => -1,
-            Color::Empty => 0,
-        };
+const MAX_DEPTH: i32 = 64;
+const MAX_PLY: usize = 128;
 
-        match piece.typ {
-            PieceType::Rook   => minors += 1,
-            PieceType::Knight => minors += 1,
-            PieceType::Bishop => minors += 1,
-            PieceType::Queen  => queens += 1,
-            _  => (),
-        }
-    }
-    
-    for square in 0..64 {
-        let piece = board.get_piece(square);
-        if queens == 0 || (queens <= 2 && minors <= 2) {
-            result += match piece.typ {
-                PieceType::King  => 900 + match piece.color {
-                    Color::White => piece_square_tables::WHITE_KING_END_GAME[square],
-                    Color::Black => piece_square_tables::BLACK_KING_END_GAME[square],
-                    Color::Empty => 0,
-                },
-                _  => 0,
-            } * match piece.color {
-                Color::White => 1,
-                Color::Black => -1,
-                Color::Empty => 0,
-            };
-        } else {
-            result += match piece.typ {
-                PieceType::King  => 900 + match piece.color {
-                    Color::White => piece_square_tables::WHITE_KING[square],
-                    Color::Black => piece_square_tables::BLACK_KING[square],
-                    Color::Empty => 0,
-                },
-                _  => 0,
-            } * match piece.color {
-                Color::White => 1,
-                Color::Black => -1,
-                Color::Empty => 0,
-            };
+/// Mate in n plies is scored MATE - n, so shorter mates are preferred
+pub const MATE: i32 = 32_000;
+const INFINITY: i32 = 32_001;
+const MATE_BOUND: i32 = MATE - MAX_PLY as i32;
+
+const NULL_MOVE_MIN_DEPTH: i32 = 3;
+// Below this depth a null move cutoff is only verified in zugzwang-prone positions
+const NULL_MOVE_VERIFICATION_DEPTH: i32 = 12;
+const REVERSE_FUTILITY_MAX_DEPTH: i32 = 6;
+const REVERSE_FUTILITY_MARGIN: i32 = 80;
+const FUTILITY_MAX_DEPTH: i32 = 4;
+const FUTILITY_MARGIN: i32 = 100;
+const LATE_MOVE_PRUNING_MAX_DEPTH: i32 = 8;
+const LATE_MOVE_REDUCTION_MIN_DEPTH: i32 = 3;
+const SINGULAR_EXTENSION_MIN_DEPTH: i32 = 8;
+const ASPIRATION_MIN_DEPTH: i32 = 5;
+const ASPIRATION_WINDOW: i32 = 25;
+
+// Helper threads skip blocks of depths, so that they spread out over several depths instead of
+// all searching the same one. Helper n uses entry (n - 1) % 20
+const SKIP_SIZE: [i32; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+const SKIP_PHASE: [i32; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+// How often a thread publishes its node count to the others
+const NODE_COUNT_INTERVAL: u64 = 1024;
+// The move currently searched at the root is reported once the search has run this long
+const CURRMOVE_MIN_TIME: u128 = 1000;
+
+// Shared by the threads of one search, for reporting
+struct SearchShared {
+    start: Instant,
+    // One counter per thread
+    nodes: Vec<AtomicU64>,
+}
+
+impl SearchShared {
+    fn new(threads: usize) -> Self {
+        SearchShared {
+            start: Instant::now(),
+            nodes: (0..threads).map(|_| AtomicU64::new(0)).collect(),
         }
     }
 
-    result
+    fn nodes(&self) -> u64 {
+        self.nodes.iter().map(|nodes| nodes.load(Ordering::Relaxed)).sum()
+    }
+}
+
+#[derive(Clone)]
+struct RootMove {
+    mov: Move,
+    score: i32,
+    pv: Vec<Move>,
+    // The deepest ply reached in this iteration's search of the line, up to and including this move
+    seldepth: usize,
 }
 
-fn is_check(board: &mut Board, check_bitboard: u64) -> bool {
-    let check_moves = board.generate_moves();
-    for check_move in check_moves {
-        if check_bitboard & (1 << check_move.end_square) > 0 {
-            return false;
+impl RootMove {
+    fn new(mov: Move) -> Self {
+        RootMove { pv: vec![mov], mov, score: 0, seldepth: 0 }
+    }
+}
+
+/// Lets the input loop stop a running search, or tell it that the pondered move was played
+pub struct SearchControl {
+    stop: AtomicBool,
+    pondering: AtomicBool,
+    print_info: bool,
+}
+
+impl SearchControl {
+    /// A control for a search that starts out pondering when `ponder` is set.
+    pub fn new(ponder: bool) -> Self {
+        SearchControl {
+            stop: AtomicBool::new(false),
+            pondering: AtomicBool::new(ponder),
+            print_info: true,
         }
     }
 
-    true
+    /// For searches whose info lines nobody reads
+    pub fn silent() -> Self {
+        SearchControl { print_info: false, ..SearchControl::new(false) }
+    }
+
+    /// Makes the search return its best move as soon as possible.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// The pondered move was played, the search goes on under its normal time limits.
+    pub fn ponderhit(&self) {
+        self.pondering.store(false, Ordering::Relaxed);
+    }
 }
 
-fn is_legal_position(board: &mut Board, castling_bitboard: u64) -> bool {
-    let own_pieces = if board.turn == Color::White {
-        board.black_pieces
+/// The outcome of `search`, `to_uci` gives the line to send to the GUI.
+pub struct SearchResult {
+    /// `EMPTY_MOVE` when the side to move has no legal move.
+    pub best_move: Move,
+    /// The expected reply to the best move, if one is known.
+    pub ponder_move: Option<Move>,
+    /// The score of the best move in centipawns, seen from the side to move.
+    pub score: i32,
+    /// The nodes searched by all threads.
+    pub nodes: u64,
+}
+
+impl SearchResult {
+    /// The bestmove line, with the ponder move if there is one.
+    pub fn to_uci(&self) -> String {
+        if self.best_move == EMPTY_MOVE {
+            return "bestmove 0000".to_string();
+        }
+        match &self.ponder_move {
+            Some(ponder_move) => format!("bestmove {} ponder {}", self.best_move, ponder_move),
+            None => format!("bestmove {}", self.best_move),
+        }
+    }
+}
+
+/// The state of one search thread: its tables, move ordering statistics and node count. Kept between
+/// searches by callers that search many positions in a row, like datagen.
+pub struct SearchState {
+    transposition_table: Arc<TranspositionTable>,
+    pawn_hash_table: PawnHashTable,
+    options: SearchOptions,
+    nodes: u64,
+    max_nodes: u64,
+    killers: Vec<[Move; 2]>,
+    // White, Black
+    history: Vec<HistoryTable>,
+    // Indexed by the start and end square of the move being answered
+    counter_moves: Vec<Move>,
+    // Indexed by depth and move number
+    reductions: Vec<[i32; 64]>,
+    // Null moves are not tried above this ply while a null move cutoff is being verified
+    null_move_min_ply: usize,
+    // The move left out at each ply by a singular extension search
+    excluded_moves: Vec<Move>,
+    root_depth: i32,
+    // The number of MultiPV lines already found in the current iteration
+    pv_index: usize,
+    print_info: bool,
+    shared: Arc<SearchShared>,
+    thread_index: usize,
+    seldepth: usize,
+    // Triangular table of principal variations, the best line found from each ply
+    pv: Vec<Vec<Move>>,
+}
+
+impl SearchState {
+    /// A fresh state using the transposition table of the options.
+    pub fn new(options: SearchOptions) -> Self {
+        SearchState {
+            transposition_table: options.transposition_table.clone(),
+            pawn_hash_table: PawnHashTable::new(),
+            options,
+            nodes: 0,
+            max_nodes: u64::MAX,
+            killers: vec![[EMPTY_MOVE, EMPTY_MOVE]; MAX_PLY],
+            history: vec![[[0; 64]; 64]; 2],
+            counter_moves: vec![EMPTY_MOVE; 64 * 64],
+            reductions: (0..=MAX_DEPTH as usize)
+                .map(|depth| std::array::from_fn(|move_number| {
+                    if depth == 0 || move_number == 0 {
+                        0
+                    } else {
+                        (0.75 + f64::ln(depth as f64) * f64::ln(move_number as f64) / 2.25) as i32
+                    }
+                }))
+                .collect(),
+            null_move_min_ply: 0,
+            excluded_moves: vec![EMPTY_MOVE; MAX_PLY],
+            root_depth: 0,
+            pv_index: 0,
+            print_info: true,
+            shared: Arc::new(SearchShared::new(1)),
+            thread_index: 0,
+            seldepth: 0,
+            pv: vec![Vec::new(); MAX_PLY + 1],
+        }
+    }
+
+    fn count_node(&mut self, ply: usize) {
+        self.nodes += 1;
+        self.seldepth = max(self.seldepth, ply);
+        if self.nodes.is_multiple_of(NODE_COUNT_INTERVAL) {
+            self.publish_nodes();
+        }
+    }
+
+    fn publish_nodes(&self) {
+        self.shared.nodes[self.thread_index].store(self.nodes, Ordering::Relaxed);
+    }
+
+    // Only the main thread talks to the GUI
+    fn reports(&self) -> bool {
+        self.print_info && self.thread_index == 0
+    }
+
+    fn update_pv(&mut self, ply: usize, mov: &Move) {
+        let (lines, deeper) = self.pv.split_at_mut(ply + 1);
+        lines[ply].clear();
+        lines[ply].push(*mov);
+        lines[ply].extend_from_slice(&deeper[0]);
+    }
+
+    fn stopped(&self, stopped: &Stop) -> bool {
+        stopped.get() || self.nodes >= self.max_nodes
+    }
+
+    #[cfg(feature = "nnue")]
+    fn nnue_network(&self) -> Option<std::sync::Arc<nnue::Network>> {
+        if self.options.use_nnue {
+            self.options.network.clone()
+        } else {
+            None
+        }
+    }
+
+    fn evaluate(&mut self, board: &Board) -> i32 {
+        #[cfg(feature = "nnue")]
+        if self.nnue_network().is_some() {
+            return nnue::evaluate(board);
+        }
+        evaluate(board, &mut self.pawn_hash_table)
+    }
+
+    // Seen from the side to move
+    fn evaluate_relative(&mut self, board: &Board) -> i32 {
+        let evaluation = self.evaluate(board);
+        if board.turn == Color::White { evaluation } else { -evaluation }
+    }
+
+    fn history_index(color: Color) -> usize {
+        if color == Color::White { 0 } else { 1 }
+    }
+
+    // Pulls the score towards the bonus, so no entry can grow past MAX_HISTORY
+    fn update_history(&mut self, color: Color, mov: &Move, bonus: i32) {
+        let entry = &mut self.history[Self::history_index(color)][mov.start_square() as usize][mov.end_square() as usize];
+        *entry += bonus - *entry * bonus.abs() / MAX_HISTORY;
+    }
+
+    fn update_quiet_statistics(&mut self, board: &Board, mov: &Move, previous_move: &Move, failed_quiets: &[Move], depth: i32, ply: usize) {
+        if self.killers[ply][0] != *mov {
+            self.killers[ply][1] = self.killers[ply][0];
+            self.killers[ply][0] = *mov;
+        }
+        if *previous_move != EMPTY_MOVE {
+            self.counter_moves[previous_move.start_square() as usize * 64 + previous_move.end_square() as usize] = *mov;
+        }
+
+        let bonus = min(depth * depth, MAX_HISTORY);
+        self.update_history(board.turn, mov, bonus);
+        for failed in failed_quiets {
+            self.update_history(board.turn, failed, -bonus);
+        }
+    }
+}
+
+// Mate scores are stored relative to the node, so they stay correct when reached at another ply
+fn score_to_transposition_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
     } else {
-        board.white_pieces
-    };
+        score
+    }
+}
 
-    is_check(board, (board.kings & own_pieces) | castling_bitboard)
+fn score_from_transposition_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
 }
 
-pub async fn search(max_depth: i32, mut time: u64, board: &mut Board, alpha_beta: bool) -> Move {
-    let best =
-        if board.turn == Color::White {
-            i32::MAX
+// Searches the root moves to depth, in the order of the previous iteration, and returns them best first.
+// Stops at the first move that reaches beta, the remaining moves are then left out
+fn search_root(depth: i32, mut alpha: i32, beta: i32, board: &mut Board, root_moves: &[Move], state: &mut SearchState, stopped: &Stop) -> Vec<RootMove> {
+    let original_alpha = alpha;
+    let mut result: Vec<RootMove> = Vec::new();
+    state.root_depth = depth;
+
+    for (index, mov) in root_moves.iter().enumerate() {
+        if state.reports() && state.shared.start.elapsed().as_millis() > CURRMOVE_MIN_TIME {
+            println!("info depth {} currmove {} currmovenumber {}", depth, mov, state.pv_index + index + 1);
+        }
+        board.make_move(*mov);
+        let score = if !state.options.alpha_beta {
+            -negamax(depth - 1, 1, -INFINITY, INFINITY, board, mov, state, stopped)
+        } else if result.is_empty() {
+            -negamax(depth - 1, 1, -beta, -alpha, board, mov, state, stopped)
         } else {
-            i32::MIN
-        };
-    if time == 0 {
-        time = u64::MAX;
-    }
-    
-    let mut board = board.clone();
-    let mut transposition_table: TranspositionTable = TranspositionTable::new();
-    let handle = stoppable_thread::spawn(move |stopped| {
-        let mut depth = 1;
-        let mut moves = board.generate_moves();
-        let mut result = Vec::new();
-        if max_depth == -1 {
-            loop {
-                let new_result = min_max(depth, &mut board, moves, best, alpha_beta, &mut transposition_table, stopped);
-                moves = new_result.iter().map(|(mov, _)| mov.clone()).collect::<Vec<Move>>();
-                if stopped.get() {
-                    return result;
-                } else {
-                    result = new_result;
-                }
-                println!("info depth {} {}", depth, result.iter().map(|(mov, _)| Board::print_move(mov)).collect::<Vec<String>>().join(" ").replace("  ", " "));
-                depth += 1;
+            // Later moves only have to be proven worse than the best one, which a null window does cheaply
+            let score = -negamax(depth - 1, 1, -alpha - 1, -alpha, board, mov, state, stopped);
+            if score > alpha && score < beta {
+                -negamax(depth - 1, 1, -beta, -alpha, board, mov, state, stopped)
+            } else {
+                score
             }
+        };
+        board.unmake_move(*mov);
+
+        if state.stopped(stopped) {
+            break;
+        }
+        let mut root_move = RootMove::new(*mov);
+        root_move.score = score;
+        root_move.seldepth = state.seldepth;
+        if result.is_empty() || score > alpha {
+            root_move.pv.extend_from_slice(&state.pv[1]);
+        }
+        alpha = max(alpha, score);
+        result.push(root_move);
+        if alpha >= beta && state.options.alpha_beta {
+            break;
+        }
+    }
+
+    // Stable, so moves that failed low keep their previous order
+    result.sort_by_key(|root_move| -root_move.score);
+    // Later MultiPV lines leave out the better moves, so they say nothing about the root position
+    if let Some(RootMove { mov: best_move, score, .. }) = result.first().filter(|_| state.pv_index == 0) {
+        let bound = if *score >= beta {
+            Bound::Lower
+        } else if *score > original_alpha {
+            Bound::Exact
         } else {
-            while depth < max_depth {
-                let new_result = min_max(depth, &mut board, moves, best, alpha_beta, &mut transposition_table, stopped);
-                moves = new_result.iter().map(|(mov, _)| mov.clone()).collect::<Vec<Move>>();
-                if stopped.get() {
-                    return result;
+            Bound::Upper
+        };
+        state.transposition_table.store(board.zobrist_key(), TranspositionTableContent {
+            best_move: *best_move,
+            score: score_to_transposition_table(*score, 0),
+            depth,
+            bound,
+        });
+    }
+    result
+}
+
+fn format_score(score: i32) -> String {
+    if score >= MATE_BOUND {
+        format!("mate {}", (MATE - score + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        format!("mate -{}", (MATE + score) / 2)
+    } else {
+        format!("cp {score}")
+    }
+}
+
+// bound is empty for an exact score, otherwise " lowerbound" or " upperbound"
+fn print_info(depth: i32, multipv: usize, root_move: &RootMove, bound: &str, state: &SearchState) {
+    state.publish_nodes();
+    let nodes = state.shared.nodes();
+    let time = state.shared.start.elapsed().as_millis() as u64;
+    println!(
+        "info depth {} seldepth {} multipv {} score {}{} nodes {} nps {} time {} hashfull {} pv {}",
+        depth,
+        root_move.seldepth,
+        multipv,
+        format_score(root_move.score),
+        bound,
+        nodes,
+        nodes * 1000 / max(time, 1),
+        time,
+        state.transposition_table.hashfull(),
+        root_move.pv.iter().map(Move::to_string).collect::<Vec<String>>().join(" "),
+    );
+}
+
+// Returns the root moves with scores seen from the side to move, and the last completed depth
+fn iterative_deepening(limits: &SearchLimits, board: &mut Board, state: &mut SearchState, stopped: &Stop) -> (Vec<RootMove>, i32) {
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+    let thread_index = state.thread_index;
+    state.max_nodes = limits.nodes.unwrap_or(u64::MAX);
+
+    let mut moves = board.generate_legal_moves().to_vec();
+    if !limits.search_moves.is_empty() {
+        moves.retain(|mov| limits.search_moves.contains(mov));
+    }
+    let mut result = moves.iter().cloned().map(RootMove::new).collect::<Vec<RootMove>>();
+    let mut completed_depth = 0;
+    if moves.is_empty() {
+        return (result, completed_depth);
+    }
+
+    for depth in 1..=max_depth {
+        if thread_index > 0 && depth < max_depth {
+            let skip = (thread_index - 1) % SKIP_SIZE.len();
+            if ((depth + SKIP_PHASE[skip]) / SKIP_SIZE[skip]) % 2 != 0 {
+                continue;
+            }
+        }
+
+        // MultiPV: the best move is searched first, then the best of the remaining moves and so on,
+        // every line to an exact score
+        let lines = min(state.options.multi_pv, moves.len());
+        let mut new_result: Vec<RootMove> = Vec::new();
+        let mut partial = None;
+        for pv_index in 0..lines {
+            state.pv_index = pv_index;
+            state.seldepth = 0;
+
+            // Aspiration windows: expect the score to stay close to the previous iteration's,
+            // and widen the window on whichever side it turns out not to
+            let previous_score = result[pv_index].score;
+            let mut delta = ASPIRATION_WINDOW;
+            let (mut alpha, mut beta) = if depth >= ASPIRATION_MIN_DEPTH && state.options.alpha_beta && previous_score.abs() < MATE_BOUND {
+                (previous_score - delta, previous_score + delta)
+            } else {
+                (-INFINITY, INFINITY)
+            };
+
+            let searched = loop {
+                let searched = search_root(depth, alpha, beta, board, &moves[pv_index..], state, stopped);
+                if state.stopped(stopped) {
+                    break searched;
+                }
+
+                let best = searched[0].clone();
+                if best.score <= alpha {
+                    if state.reports() {
+                        print_info(depth, pv_index + 1, &best, " upperbound", state);
+                    }
+                    beta = (alpha + beta) / 2;
+                    alpha = max(best.score - delta, -INFINITY);
+                } else if best.score >= beta {
+                    if state.reports() {
+                        print_info(depth, pv_index + 1, &best, " lowerbound", state);
+                    }
+                    beta = min(best.score + delta, INFINITY);
+                    moves.retain(|mov| *mov != best.mov);
+                    moves.insert(pv_index, best.mov);
                 } else {
-                    result = new_result;
+                    break searched;
                 }
-                println!("info depth {} {:?}", depth, result.iter().map(|(mov, _)| Board::print_move(mov)).collect::<Vec<String>>());
-                depth += 1;
+                delta += delta / 2;
+            };
+
+            if state.stopped(stopped) {
+                // The previous best move is searched first, so a partial search can only improve on it,
+                // unless it failed low, when every score is just an upper bound
+                partial = searched.into_iter().next().filter(|root_move| root_move.score > alpha);
+                break;
             }
-            result
+            new_result.truncate(pv_index);
+            new_result.extend(searched);
+            moves = new_result.iter().map(|root_move| root_move.mov).collect::<Vec<Move>>();
         }
-    });
-
-    tokio::time::sleep(tokio::time::Duration::from_millis(time)).await;
+        state.pv_index = 0;
 
-    let result = handle.stop().join().unwrap();
-    result[0].0.clone()
+        if state.stopped(stopped) {
+            // Lines completed in this iteration are kept ahead of the previous iteration's
+            let mut merged = new_result;
+            merged.extend(partial);
+            result.retain(|root_move| merged.iter().all(|line| line.mov != root_move.mov));
+            merged.append(&mut result);
+            result = merged;
+            break;
+        }
+        // A line searched with the better moves left out can still come back with a higher score than the
+        // lines before it, the lines are reported and searched next iteration in the order of their scores
+        new_result[..lines].sort_by_key(|root_move| Reverse(root_move.score));
+        moves = new_result.iter().map(|root_move| root_move.mov).collect::<Vec<Move>>();
+        result = new_result;
+        completed_depth = depth;
+        if state.reports() {
+            for (pv_index, root_move) in result.iter().take(lines).enumerate() {
+                print_info(depth, pv_index + 1, root_move, "", state);
+            }
+        }
+        if limits.mate.is_some_and(|moves| result[0].score >= MATE - (2 * moves - 1)) {
+            break;
+        }
+    }
+    (result, completed_depth)
 }
 
-pub async fn alpha_beta_test(board: &mut Board) {
-    let depth = 1;
-    println!("{}", Board::print_move(&search(depth, 0, board, false).await));
-    println!("{}", Board::print_move(&search(depth, 0, board, true).await));
+// Each thread votes for its best move, weighted by how deep it got and how good it found the move.
+// Returns the winning move as found by the deepest thread that picked it
+fn vote(results: &[(Vec<RootMove>, i32)]) -> Option<&RootMove> {
+    let min_score = results.iter().filter_map(|(result, _)| result.first()).map(|root_move| root_move.score).min().unwrap_or(0);
+    let mut votes: HashMap<Move, i64> = HashMap::new();
+    for (result, completed_depth) in results {
+        if let Some(root_move) = result.first() {
+            *votes.entry(root_move.mov).or_insert(0) += (root_move.score as i64 - min_score as i64 + 14) * *completed_depth as i64;
+        }
+    }
+
+    // The main thread wins ties, it is the one whose search was reported to the GUI
+    let mut best = results[0].0.first();
+    let mut best_depth = results[0].1;
+    for (result, completed_depth) in results {
+        if let Some(root_move) = result.first() {
+            let best_votes = best.map_or(0, |best| votes[&best.mov]);
+            let same_move_deeper = best.is_some_and(|best| best.mov == root_move.mov && *completed_depth > best_depth);
+            if votes[&root_move.mov] > best_votes || same_move_deeper {
+                best = Some(root_move);
+                best_depth = *completed_depth;
+            }
+        }
+    }
+    best
 }
 
-pub fn perft(start_depth: i32, depth: i32, board: &mut Board) -> i32 {
-    if depth == 0 {
-        return 1
+// The second move of the principal variation, or the transposition table move after the best move
+fn ponder_move(board: &mut Board, best: &RootMove, transposition_table: &TranspositionTable) -> Option<Move> {
+    if let Some(mov) = best.pv.get(1) {
+        return Some(*mov);
     }
 
-    let mut result = 0;
+    board.make_move(best.mov);
+    let ponder_move = transposition_table
+        .probe(board.zobrist_key())
+        .map(|entry| entry.best_move)
+        .filter(|mov| *mov != EMPTY_MOVE && board.generate_legal_moves().contains(mov));
+    board.unmake_move(best.mov);
+    ponder_move
+}
 
-    for mov in board.generate_moves() {
-        let mut castling_bitboard = 0;
-        if board.get_piece(mov.start_square).typ == PieceType::King 
-            && i32::abs((mov.end_square % 8) as i32 - (mov.start_square % 8) as i32) == 2 {
-            for square in min(mov.start_square, mov.end_square)..max(mov.start_square, mov.end_square) {
-                castling_bitboard |= 1 << square;
-            }
-        }
+/// While pondering the time limit does not run, it starts from the ponderhit instead,
+/// and the result is held back until then even if the search finishes.
+/// In deterministic mode the time limits are ignored when there is a depth or node limit, so that the result
+/// depends on nothing but the input. Searches without one still keep to the clock
+pub async fn search(limits: &SearchLimits, board: &mut Board, options: &SearchOptions, control: &SearchControl) -> SearchResult {
+    let (threads, time) = if options.deterministic {
+        options.transposition_table.clear();
+        let fixed_size = limits.depth.is_some() || limits.nodes.is_some();
+        (1, if fixed_size { None } else { limits.time(board.turn) })
+    } else {
+        (options.threads, limits.time(board.turn))
+    };
 
-        board.make_move(mov.clone());
-        if is_legal_position(board, castling_bitboard) {
-            let current_move = perft(start_depth, depth - 1, board);
-            result += current_move;
+    // Lazy SMP: every thread runs its own iterative deepening and they only share the transposition table
+    let shared = Arc::new(SearchShared::new(threads));
+    let finished = Arc::new(AtomicBool::new(false));
+    let handles = (0..threads).map(|thread_index| {
+        let mut board = board.clone();
+        let mut state = SearchState::new(options.clone());
+        state.shared = shared.clone();
+        state.thread_index = thread_index;
+        state.print_info = control.print_info;
+
+        #[cfg(feature = "nnue")]
+        if let Some(network) = state.nnue_network() {
+            board.enable_nnue(network);
+        }
 
-            if start_depth == depth {
-                println!("{}: {}", Board::print_move(&mov), current_move);
+        let finished = finished.clone();
+        let limits = limits.clone();
+        stoppable_thread::spawn(move |stopped| {
+            let result = iterative_deepening(&limits, &mut board, &mut state, stopped);
+            state.publish_nodes();
+            if thread_index == 0 {
+                finished.store(true, Ordering::Relaxed);
             }
+            result
+        })
+    }).collect::<Vec<_>>();
+
+    // Wake up regularly, so a depth limited search does not wait for the full time
+    let mut start = Instant::now();
+    while !control.stop.load(Ordering::Relaxed) {
+        if control.pondering.load(Ordering::Relaxed) {
+            start = Instant::now();
+        } else if (finished.load(Ordering::Relaxed) && !limits.infinite)
+            || time.is_some_and(|time| start.elapsed().as_millis() as u64 >= time)
+            // Each thread stops at the node limit by itself, together they are stopped here
+            || limits.nodes.is_some_and(|nodes| shared.nodes() >= nodes) {
+            break;
         }
-        board.unmake_move(mov);
+        tokio::time::sleep(tokio::time::Duration::from_millis(time.map_or(5, |time| min(time, 5)))).await;
     }
 
-    result
+    let results = handles
+        .into_iter()
+        .map(|handle| handle.stop())
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect::<Vec<_>>();
+    match vote(&results) {
+        Some(best) => SearchResult {
+            best_move: best.mov,
+            ponder_move: ponder_move(board, best, &options.transposition_table),
+            score: best.score,
+            nodes: shared.nodes(),
+        },
+        None => SearchResult { best_move: EMPTY_MOVE, ponder_move: None, score: 0, nodes: shared.nodes() },
+    }
+}
+
+/// Runs on the calling thread and stops after a fixed number of nodes, scores are seen from White
+pub fn search_nodes(max_nodes: u64, board: &mut Board, state: &mut SearchState) -> Vec<(Move, i32)> {
+    state.transposition_table.clear();
+    state.nodes = 0;
+    let sign = if board.turn == Color::White { 1 } else { -1 };
+    state.print_info = false;
+    let limits = SearchLimits { nodes: Some(max_nodes), ..SearchLimits::default() };
+    iterative_deepening(&limits, board, state, &Stop::new(false))
+        .0
+        .into_iter()
+        .map(|root_move| (root_move.mov, root_move.score * sign))
+        .collect()
+}
+
+/// Prints the best move of a depth 1 search without and with alpha-beta pruning, which must agree.
+pub async fn alpha_beta_test(board: &mut Board, options: &SearchOptions) {
+    let limits = SearchLimits { depth: Some(1), ..SearchLimits::default() };
+    let mut options = options.clone();
+    options.alpha_beta = false;
+    println!("{}", search(&limits, board, &options, &SearchControl::new(false)).await.best_move);
+    options.alpha_beta = true;
+    println!("{}", search(&limits, board, &options, &SearchControl::new(false)).await.best_move);
+}
+
+fn non_pawn_pieces(board: &Board) -> u32 {
+    let own_pieces = if board.turn == Color::White { board.white_pieces } else { board.black_pieces };
+    (own_pieces & !board.pawns & !board.kings).count()
 }
 
-fn min_max(depth: i32, board: &mut Board, mut moves: Vec<Move>, parent_score: i32, alpha_beta: bool, transposition_table: &mut TranspositionTable, stopped: &Stop) -> Vec<(Move, i32)> {
-    if stopped.get() {
-        return Vec::new();
+#[allow(clippy::too_many_arguments)]
+fn negamax(depth: i32, ply: usize, mut alpha: i32, beta: i32, board: &mut Board, previous_move: &Move, state: &mut SearchState, stopped: &Stop) -> i32 {
+    state.pv[ply].clear();
+    if depth <= 0 {
+        return quiescence(ply, alpha, beta, board, state, stopped);
+    }
+    if state.stopped(stopped) {
+        return 0;
+    }
+    state.count_node(ply);
+    if ply >= MAX_PLY {
+        return state.evaluate_relative(board);
+    }
+
+    let key = board.zobrist_key();
+    let excluded_move = state.excluded_moves[ply];
+    let mut transposition_move = EMPTY_MOVE;
+    // The score and bound of a transposition table hit, if the entry is deep enough to try a singular extension
+    let mut singular_candidate = None;
+    let pv_node = beta - alpha > 1;
+    if let Some(entry) = state.transposition_table.probe(key).filter(|_| excluded_move == EMPTY_MOVE) {
+        transposition_move = entry.best_move;
+        let score = score_from_transposition_table(entry.score, ply);
+        if entry.depth >= depth - 3 && entry.bound != Bound::Upper && score.abs() < MATE_BOUND {
+            singular_candidate = Some(score);
+        }
+        // Principal variation nodes are searched anyway, so that the whole line can be reported
+        if entry.depth >= depth && !pv_node && match entry.bound {
+            Bound::Exact => true,
+            Bound::Lower => score >= beta,
+            Bound::Upper => score <= alpha,
+        } {
+            return score;
+        }
+    }
+
+    let in_check = board.in_check();
+    let selective = state.options.alpha_beta && !pv_node && !in_check && excluded_move == EMPTY_MOVE;
+    let static_evaluation = if in_check { -INFINITY } else { state.evaluate_relative(board) };
+
+    // Reverse futility pruning: far enough above beta that no quiet move will bring the score back down
+    if selective
+        && state.options.reverse_futility_pruning
+        && depth <= REVERSE_FUTILITY_MAX_DEPTH
+        && beta.abs() < MATE_BOUND
+        && static_evaluation - REVERSE_FUTILITY_MARGIN * depth >= beta {
+        return static_evaluation;
+    }
+
+    // Null-move pruning: if passing still fails high, a real move almost certainly would too
+    let non_pawn_pieces = non_pawn_pieces(board);
+    if selective
+        && state.options.null_move_pruning
+        && depth >= NULL_MOVE_MIN_DEPTH
+        && *previous_move != EMPTY_MOVE
+        && ply >= state.null_move_min_ply
+        && non_pawn_pieces > 0
+        && static_evaluation >= beta {
+        let reduction = 3 + depth / 6;
+        board.make_null_move();
+        let score = -negamax(depth - 1 - reduction, ply + 1, -beta, -beta + 1, board, &EMPTY_MOVE, state, stopped);
+        board.unmake_null_move();
+
+        if state.stopped(stopped) {
+            return 0;
+        }
+        if score >= beta {
+            let score = if score >= MATE_BOUND { beta } else { score };
+            // With a single piece left, zugzwang is common enough to check the cutoff with a normal search
+            if non_pawn_pieces > 1 && depth < NULL_MOVE_VERIFICATION_DEPTH {
+                return score;
+            }
+            state.null_move_min_ply = ply + (3 * (depth - reduction) / 4) as usize;
+            let verification = negamax(depth - 1 - reduction, ply, beta - 1, beta, board, previous_move, state, stopped);
+            state.null_move_min_ply = 0;
+            if verification >= beta {
+                return score;
+            }
+        }
     }
-    if depth == 0 {
-        vec![(EMPTY_MOVE, evaluate(board))]
+
+    let original_alpha = alpha;
+    let mut best_score = -INFINITY;
+    let mut best_move = EMPTY_MOVE;
+    let mut legal_moves = 0;
+    let mut failed_quiets = Vec::new();
+    let mut quiet_moves = 0;
+
+    let counter_move = if *previous_move == EMPTY_MOVE {
+        EMPTY_MOVE
     } else {
-        let previous_search = transposition_table.get(board);
-        match previous_search {
-            Some(value) => {
-                if value.depth >= depth {
-                    return value.result.clone();
+        state.counter_moves[previous_move.start_square() as usize * 64 + previous_move.end_square() as usize]
+    };
+    let killers = state.killers[ply];
+    let mut picker = MovePicker::new(board, in_check, transposition_move, killers, counter_move);
+    let history_index = SearchState::history_index(board.turn);
+    // The previous move's captured piece, if any, is the last entry of the move history
+    let previous_capture = previous_move.is_capture();
+    // Extensions are not given past twice the iteration depth, so that forcing lines cannot grow without bound
+    let can_extend = ply < 2 * state.root_depth as usize;
+
+    while let Some(mov) = picker.next(board, &state.history[history_index]) {
+        if mov == excluded_move || !board.is_legal(&mov) {
+            continue;
+        }
+        legal_moves += 1;
+        let capture = mov.is_capture();
+        let quiet = !capture && !mov.is_promotion();
+        let history = state.history[history_index][mov.start_square() as usize][mov.end_square() as usize];
+
+        let mut extension = 0;
+        if can_extend {
+            // Singular extension: the transposition table move is the only one that holds the score
+            if let Some(transposition_score) = singular_candidate.filter(|_| {
+                state.options.singular_extension && mov == transposition_move && ply > 0 && depth >= SINGULAR_EXTENSION_MIN_DEPTH
+            }) {
+                let singular_beta = transposition_score - 2 * depth;
+                state.excluded_moves[ply] = mov;
+                let score = negamax((depth - 1) / 2, ply, singular_beta - 1, singular_beta, board, previous_move, state, stopped);
+                state.excluded_moves[ply] = EMPTY_MOVE;
+                if score < singular_beta {
+                    extension = 1;
                 }
-            },
-            None => (),
+            }
+            if state.options.recapture_extension && previous_capture && capture && mov.end_square() == previous_move.end_square() {
+                extension = 1;
+            }
+            if state.options.passed_pawn_extension
+                && board.pawns.contains(mov.start_square())
+                && relative_rank(mov.end_square(), board.turn) == 6 {
+                extension = 1;
+            }
+        }
+        if quiet {
+            quiet_moves += 1;
         }
-        let mut result = Vec::new();
-        let mut best =
-            if board.turn == Color::White {
-                i32::MIN
-            } else {
-                i32::MAX
-            };
 
-        let turn = board.turn;
-
-        moves = moves
-            .iter()
-            .filter(|mov| {
-                board.make_move((*mov).clone());
-                let mut castling_bitboard = 0;
-                if board.get_piece(mov.start_square).typ == PieceType::King 
-                    && i32::abs((mov.end_square % 8) as i32 - (mov.start_square % 8) as i32) == 2 {
-                    for square in min(mov.start_square, mov.end_square)..max(mov.start_square, mov.end_square) {
-                        castling_bitboard |= 1 << square;
-                    }
+        board.make_move(mov);
+        let gives_check = board.in_check();
+        if can_extend && gives_check && state.options.check_extension {
+            extension = 1;
+        }
+        let new_depth = depth - 1 + extension;
+
+        // Quiet moves late in the list of a node that is not expected to matter are skipped
+        if selective && quiet && !gives_check && legal_moves > 1 && best_score > -MATE_BOUND {
+            let futile = state.options.futility_pruning
+                && depth <= FUTILITY_MAX_DEPTH
+                && static_evaluation + FUTILITY_MARGIN * (depth + 1) <= alpha;
+            let late = state.options.late_move_pruning
+                && depth <= LATE_MOVE_PRUNING_MAX_DEPTH
+                && quiet_moves > 3 + depth * depth;
+            if futile || late {
+                board.unmake_move(mov);
+                continue;
+            }
+        }
+
+        let score = if !state.options.alpha_beta {
+            -negamax(new_depth, ply + 1, -INFINITY, INFINITY, board, &mov, state, stopped)
+        } else if legal_moves == 1 {
+            -negamax(new_depth, ply + 1, -beta, -alpha, board, &mov, state, stopped)
+        } else {
+            // Late move reductions: quiet moves ordered late are searched shallower first
+            let mut reduction = 0;
+            if state.options.late_move_reductions && quiet && depth >= LATE_MOVE_REDUCTION_MIN_DEPTH && !in_check && extension == 0 {
+                reduction = state.reductions[min(depth, MAX_DEPTH) as usize][min(legal_moves, 63)];
+                if pv_node {
+                    reduction -= 1;
                 }
-                let legal = is_legal_position(board, castling_bitboard);
-                board.unmake_move((*mov).clone());
-                legal
-            })
-            .map(|mov| mov.clone())
-            .collect::<Vec<Move>>();
-        
-        if moves.len() == 0 {
-            let evaluation = i32::MAX * match board.turn {
-                Color::White => -1,
-                Color::Black => 1,
-                Color::Empty => 0,
-            };
-            return vec![(EMPTY_MOVE, evaluation)];
-        }
-
-        for mov in moves.clone() {
-            board.make_move(mov.clone());
-            
-            let moves = board.generate_moves();
-            let min_max_result = min_max(depth - 1, board, moves, best, alpha_beta, transposition_table, stopped);
-
-            if min_max_result.len() > 0 {
-                result.push((mov.clone(), min_max_result[0].1));
-                
-                if alpha_beta {
-                    if turn == Color::White {
-                        best = i32::max(best, min_max_result[0].1);
-                        if parent_score < best {
-                            board.unmake_move(mov);
-                            break;
-                        }
-                    } else {
-                        best = i32::min(best, min_max_result[0].1);
-                        if parent_score > best {
-                            board.unmake_move(mov);
-                            break;
-                        }
-                    }
+                if killers.contains(&mov) {
+                    reduction -= 1;
                 }
+                reduction -= history / (MAX_HISTORY / 2);
+                reduction = reduction.clamp(0, new_depth - 1);
+            }
+
+            let mut score = -negamax(new_depth - reduction, ply + 1, -alpha - 1, -alpha, board, &mov, state, stopped);
+            if score > alpha && reduction > 0 {
+                score = -negamax(new_depth, ply + 1, -alpha - 1, -alpha, board, &mov, state, stopped);
+            }
+            if score > alpha && score < beta {
+                score = -negamax(new_depth, ply + 1, -beta, -alpha, board, &mov, state, stopped);
             }
+            score
+        };
+        board.unmake_move(mov);
 
-            board.unmake_move(mov);
+        if state.stopped(stopped) {
+            return 0;
         }
-        
-        result.sort_by(|(_, score1), (_, score2)| 
-            if turn == Color::White {
-                score2.partial_cmp(score1).unwrap()
-            } else {
-                score1.partial_cmp(score2).unwrap()
+
+        if score > best_score {
+            best_score = score;
+            best_move = mov;
+            if score > alpha {
+                alpha = score;
+                state.update_pv(ply, &mov);
+                if alpha >= beta && state.options.alpha_beta {
+                    if quiet {
+                        state.update_quiet_statistics(board, &mov, previous_move, &failed_quiets, depth, ply);
+                    }
+                    break;
+                }
             }
-        );
+        }
+        if quiet {
+            failed_quiets.push(mov);
+        }
+    }
 
-        transposition_table.insert(board.clone(), TranspositionTableContent {
-            result: result.clone(),
+    if legal_moves == 0 {
+        if excluded_move != EMPTY_MOVE {
+            return alpha;
+        }
+        return if in_check { -MATE + ply as i32 } else { 0 };
+    }
+
+    let bound = if best_score >= beta {
+        Bound::Lower
+    } else if best_score > original_alpha {
+        Bound::Exact
+    } else {
+        Bound::Upper
+    };
+    // A search without the excluded move says nothing about the position itself
+    if excluded_move == EMPTY_MOVE {
+        state.transposition_table.store(key, TranspositionTableContent {
+            best_move,
+            score: score_to_transposition_table(best_score, ply),
             depth,
+            bound,
         });
-        result
     }
+
+    best_score
+}
+
+// Only captures are searched, until the position is quiet enough for the static evaluation
+fn quiescence(ply: usize, mut alpha: i32, beta: i32, board: &mut Board, state: &mut SearchState, stopped: &Stop) -> i32 {
+    state.pv[ply].clear();
+    if state.stopped(stopped) {
+        return 0;
+    }
+    state.count_node(ply);
+
+    if ply >= MAX_PLY {
+        return state.evaluate_relative(board);
+    }
+
+    // In check there is no standing pat: every evasion is searched, and no evasion means mate
+    let in_check = board.in_check();
+    let mut best_score = -MATE + ply as i32;
+    if !in_check {
+        let stand_pat = state.evaluate_relative(board);
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = max(alpha, stand_pat);
+        best_score = stand_pat;
+    }
+
+    let mut picker = MovePicker::new_quiescence(board, in_check);
+    let history_index = SearchState::history_index(board.turn);
+    while let Some(mov) = picker.next(board, &state.history[history_index]) {
+        if !board.is_legal(&mov) {
+            continue;
+        }
+
+        board.make_move(mov);
+        let score = -quiescence(ply + 1, -beta, -alpha, board, state, stopped);
+        board.unmake_move(mov);
+
+        if state.stopped(stopped) {
+            return 0;
+        }
+
+        if score > best_score {
+            best_score = score;
+            if score > alpha {
+                alpha = score;
+                state.update_pv(ply, &mov);
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+    }
+
+    best_score
 }
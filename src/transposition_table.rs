@@ -0,0 +1,104 @@
+//! The transposition table, shared lock-free between the search threads.
+use crate::types::*;
+use crate::consts::*;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The size of the table until the Hash option changes it
+pub const DEFAULT_HASH_MEGABYTES: usize = 16;
+
+// Every thread reads and writes the table without locks. The key is stored xored with the data,
+// so an entry torn by two threads writing at once no longer matches its key and is ignored
+struct Entry {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/// Search results by Zobrist key, shared between the threads of a search and kept between searches.
+pub struct TranspositionTable {
+    entries: Vec<Entry>,
+}
+
+// Bits 0..16 best move, 16..32 score, 32..40 depth, 40..42 bound
+fn pack(content: &TranspositionTableContent) -> u64 {
+    let bound = match content.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+
+    content.best_move.bits() as u64
+        | (content.score as i16 as u16 as u64) << 16
+        | (content.depth.clamp(0, u8::MAX as i32) as u64) << 32
+        | bound << 40
+}
+
+fn unpack(data: u64) -> TranspositionTableContent {
+    TranspositionTableContent {
+        best_move: Move::from_bits(data as u16),
+        score: (data >> 16) as u16 as i16 as i32,
+        depth: (data >> 32 & 255) as i32,
+        bound: match data >> 40 & 3 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        },
+    }
+}
+
+impl TranspositionTable {
+    /// An empty table taking up about the given number of megabytes
+    pub fn new(megabytes: usize) -> Self {
+        let size = usize::max(megabytes * 1024 * 1024 / std::mem::size_of::<Entry>(), 1);
+        TranspositionTable {
+            entries: (0..size).map(|_| Entry { key: AtomicU64::new(0), data: AtomicU64::new(0) }).collect(),
+        }
+    }
+
+    fn entry(&self, key: u64) -> &Entry {
+        &self.entries[(key % self.entries.len() as u64) as usize]
+    }
+
+    /// The stored result for the key, if its entry holds this position
+    pub fn probe(&self, key: u64) -> Option<TranspositionTableContent> {
+        let entry = self.entry(key);
+        let data = entry.data.load(Ordering::Relaxed);
+        if data != 0 && entry.key.load(Ordering::Relaxed) ^ data == key {
+            Some(unpack(data))
+        } else {
+            None
+        }
+    }
+
+    /// Deeper results of the same position are kept, anything else is replaced
+    pub fn store(&self, key: u64, mut content: TranspositionTableContent) {
+        let entry = self.entry(key);
+        if let Some(previous) = self.probe(key) {
+            if content.bound != Bound::Exact && content.depth + 2 < previous.depth {
+                return;
+            }
+            if content.best_move == EMPTY_MOVE {
+                content.best_move = previous.best_move;
+            }
+        }
+
+        let data = pack(&content);
+        entry.key.store(key ^ data, Ordering::Relaxed);
+        entry.data.store(data, Ordering::Relaxed);
+    }
+
+    /// Permille of the first thousand entries that are in use
+    pub fn hashfull(&self) -> usize {
+        let sample = usize::min(self.entries.len(), 1000);
+        let used = self.entries[..sample].iter().filter(|entry| entry.data.load(Ordering::Relaxed) != 0).count();
+        used * 1000 / sample
+    }
+
+    /// Empties every entry, for a new game
+    pub fn clear(&self) {
+        for entry in &self.entries {
+            entry.key.store(0, Ordering::Relaxed);
+            entry.data.store(0, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! The tune subcommand, Texel tuning of the material values and piece-square tables on a set of scored
+//! positions. The other evaluation terms are only scaled as a whole, in the middlegame and the endgame.
+use crate::board::Board;
+use crate::types::*;
+use crate::consts::*;
+use crate::evaluation::{self, *};
+use crate::piece_square_tables::*;
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::ops::Range;
+use std::thread;
+
+const DEFAULT_EPOCHS: usize = 500;
+const DEFAULT_OUTPUT: &str = "tuned_piece_square_tables.rs";
+const LEARNING_RATE: f64 = 1.0;
+
+// Pawn, Rook, Knight, Bishop, Queen
+const MATERIAL_OFFSET: usize = 0;
+const MATERIAL_TERMS: usize = 5;
+// Pawn, Rook, Knight, Bishop, Queen, King, King end game, all from White's side
+const PIECE_SQUARE_OFFSET: usize = MATERIAL_OFFSET + MATERIAL_TERMS;
+const KING_END_GAME_TABLE: usize = 6;
+// Middlegame and endgame weight in percent for every trace term after the piece-square tables
+const TERM_WEIGHT_OFFSET: usize = PIECE_SQUARE_OFFSET + 7 * 64;
+const TUNED_TERMS: usize = TERM_NAMES.len() - 2;
+const PARAMETER_COUNT: usize = TERM_WEIGHT_OFFSET + TUNED_TERMS * 2;
+
+const TABLE_NAMES: [&str; 7] = ["PAWN", "ROOK", "KNIGHT", "BISHOP", "QUEEN", "KING", "KING_END_GAME"];
+
+pub struct Entry {
+    coefficients: Vec<(usize, f64)>,
+    result: f64,
+}
+
+pub fn initial_parameters() -> Vec<f64> {
+    let mut parameters = vec![0.0; PARAMETER_COUNT];
+
+    for piece in 0..5 {
+        parameters[MATERIAL_OFFSET + piece] = PIECE_VALUES[piece + 1] as f64;
+    }
+
+    let tables = [
+        WHITE_PAWN, WHITE_ROOK, WHITE_KNIGHT, WHITE_BISHOP, WHITE_QUEEN, WHITE_KING, WHITE_KING_END_GAME,
+    ];
+    for (table, values) in tables.iter().enumerate() {
+        for (square, value) in values.iter().enumerate() {
+            parameters[PIECE_SQUARE_OFFSET + table * 64 + square] = *value as f64;
+        }
+    }
+
+    for weight in &mut parameters[TERM_WEIGHT_OFFSET..] {
+        *weight = 100.0;
+    }
+
+    parameters
+}
+
+// The evaluation is linear in every parameter, so a position is stored as the factor each one is multiplied by
+fn coefficients(board: &Board) -> Vec<(usize, f64)> {
+    let phase = game_phase(board) as f64 / TOTAL_PHASE as f64;
+    let mut coefficients = Vec::new();
+
+    for square in board.white_pieces | board.black_pieces {
+        let piece = board.get_piece(square);
+        let (sign, white_square) = if piece.color == Color::White {
+            (1.0, square as usize)
+        } else {
+            (-1.0, square.flip() as usize)
+        };
+        let table = piece.typ as usize - 1;
+
+        if piece.typ == PieceType::King {
+            coefficients.push((PIECE_SQUARE_OFFSET + table * 64 + white_square, sign * phase));
+            coefficients.push((PIECE_SQUARE_OFFSET + KING_END_GAME_TABLE * 64 + white_square, sign * (1.0 - phase)));
+        } else {
+            coefficients.push((MATERIAL_OFFSET + table, sign));
+            coefficients.push((PIECE_SQUARE_OFFSET + table * 64 + white_square, sign));
+        }
+    }
+
+    for (term, (_, white, black)) in evaluation::trace(board).iter().skip(2).enumerate() {
+        let difference = *white - *black;
+        coefficients.push((TERM_WEIGHT_OFFSET + term * 2, difference.mg as f64 * phase / 100.0));
+        coefficients.push((TERM_WEIGHT_OFFSET + term * 2 + 1, difference.eg as f64 * (1.0 - phase) / 100.0));
+    }
+
+    coefficients
+}
+
+fn parse_result(text: &str) -> Option<f64> {
+    if text.contains("1/2-1/2") {
+        Some(0.5)
+    } else if text.contains("1-0") {
+        Some(1.0)
+    } else if text.contains("0-1") {
+        Some(0.0)
+    } else {
+        text.trim_matches(|c: char| c.is_whitespace() || c == '[' || c == ']' || c == '"' || c == ';')
+            .parse::<f64>()
+            .ok()
+    }
+}
+
+// Accepts "fen | score | result", "fen [result]" and EPD lines ending in c9 "result";
+fn parse_line(line: &str) -> Option<(String, f64)> {
+    let (position, result) = if line.contains('|') {
+        let parts = line.split('|').collect::<Vec<&str>>();
+        (parts[0], parse_result(parts[parts.len() - 1])?)
+    } else {
+        (line, parse_result(line.split_whitespace().last()?)?)
+    };
+
+    let fields = position.split_whitespace().collect::<Vec<&str>>();
+    if fields.len() < 4 {
+        return None;
+    }
+    let clocks = if fields.len() >= 6 && fields[4].parse::<Clock>().is_ok() && fields[5].parse::<Clock>().is_ok() {
+        fields[4..6].join(" ")
+    } else {
+        "0 1".to_string()
+    };
+
+    Some((format!("{} {}", fields[0..4].join(" "), clocks), result))
+}
+
+pub fn load_dataset(path: &str) -> Vec<Entry> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) => {
+            println!("Could not open {path}: {error}");
+            return Vec::new();
+        }
+    };
+
+    let mut board = EMPTY_BOARD;
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        if let Some((fen, result)) = parse_line(&line) {
+            board.load_fen(fen);
+            entries.push(Entry {
+                coefficients: coefficients(&board),
+                result,
+            });
+        }
+    }
+    entries
+}
+
+fn linear_evaluation(entry: &Entry, parameters: &[f64]) -> f64 {
+    entry.coefficients.iter().map(|(index, coefficient)| parameters[*index] * coefficient).sum()
+}
+
+fn sigmoid(k: f64, evaluation: f64) -> f64 {
+    1.0 / (1.0 + f64::powf(10.0, -k * evaluation / 400.0))
+}
+
+fn threads() -> usize {
+    thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1)
+}
+
+pub fn mean_squared_error(entries: &[Entry], parameters: &[f64], k: f64) -> f64 {
+    let chunk_size = entries.len().div_ceil(threads()).max(1);
+    let error: f64 = thread::scope(|scope| {
+        entries
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                chunk.iter()
+                    .map(|entry| (entry.result - sigmoid(k, linear_evaluation(entry, parameters))).powi(2))
+                    .sum::<f64>()
+            }))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .sum()
+    });
+    error / entries.len() as f64
+}
+
+pub fn gradient(entries: &[Entry], parameters: &[f64], k: f64) -> Vec<f64> {
+    let chunk_size = entries.len().div_ceil(threads()).max(1);
+    let partial_gradients = thread::scope(|scope| {
+        entries
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                let mut gradient = vec![0.0; PARAMETER_COUNT];
+                for entry in chunk {
+                    let prediction = sigmoid(k, linear_evaluation(entry, parameters));
+                    let factor = (prediction - entry.result) * prediction * (1.0 - prediction);
+                    for (index, coefficient) in &entry.coefficients {
+                        gradient[*index] += factor * coefficient;
+                    }
+                }
+                gradient
+            }))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<Vec<f64>>>()
+    });
+
+    let scale = 2.0 * k * f64::ln(10.0) / 400.0 / entries.len() as f64;
+    let mut gradient = vec![0.0; PARAMETER_COUNT];
+    for partial_gradient in partial_gradients {
+        for (total, partial) in gradient.iter_mut().zip(partial_gradient) {
+            *total += partial * scale;
+        }
+    }
+    gradient
+}
+
+pub fn find_k(entries: &[Entry], parameters: &[f64]) -> f64 {
+    let mut best_k = 1.0;
+    let mut best_error = mean_squared_error(entries, parameters, best_k);
+    let mut step = 0.1;
+
+    for _ in 0..3 {
+        let start = f64::max(best_k - step * 10.0, step);
+        for i in 0..=20 {
+            let k = start + step * i as f64;
+            let error = mean_squared_error(entries, parameters, k);
+            if error < best_error {
+                best_error = error;
+                best_k = k;
+            }
+        }
+        step /= 10.0;
+    }
+
+    best_k
+}
+
+// Pawns never stand on the first or last rank
+fn table_squares(table: usize) -> Range<usize> {
+    if table == 0 { 8..56 } else { 0..64 }
+}
+
+fn table_mean(parameters: &[f64], table: usize) -> f64 {
+    let squares = table_squares(table);
+    let count = squares.len() as f64;
+    squares.map(|square| parameters[PIECE_SQUARE_OFFSET + table * 64 + square]).sum::<f64>() / count
+}
+
+// A piece value and the mean of its table can trade any amount without changing a single evaluation, and the
+// mean of a king table makes no difference at all, so the means are held where they were and only the material
+// values move. Adam steps every parameter on its own scale and would otherwise let them drift
+fn fix_table_means(parameters: &mut [f64], means: &[f64]) {
+    for (table, mean) in means.iter().enumerate() {
+        let shift = table_mean(parameters, table) - mean;
+        for square in table_squares(table) {
+            parameters[PIECE_SQUARE_OFFSET + table * 64 + square] -= shift;
+        }
+        if table < MATERIAL_TERMS {
+            parameters[MATERIAL_OFFSET + table] += shift;
+        }
+    }
+}
+
+/// Adam on the mean squared error, for `epochs` passes over the entries
+pub fn optimize(entries: &[Entry], parameters: &mut [f64], k: f64, epochs: usize) {
+    let means = (0..TABLE_NAMES.len()).map(|table| table_mean(parameters, table)).collect::<Vec<f64>>();
+
+    // Adam keeps a per parameter step size, so rarely used squares still move
+    let mut momentum = vec![0.0; PARAMETER_COUNT];
+    let mut velocity = vec![0.0; PARAMETER_COUNT];
+    let (beta1, beta2) = (0.9, 0.999);
+
+    for epoch in 1..=epochs {
+        let gradient = gradient(entries, parameters, k);
+        for index in 0..PARAMETER_COUNT {
+            momentum[index] = beta1 * momentum[index] + (1.0 - beta1) * gradient[index];
+            velocity[index] = beta2 * velocity[index] + (1.0 - beta2) * gradient[index] * gradient[index];
+            let corrected_momentum = momentum[index] / (1.0 - f64::powi(beta1, epoch as i32));
+            let corrected_velocity = velocity[index] / (1.0 - f64::powi(beta2, epoch as i32));
+            parameters[index] -= LEARNING_RATE * corrected_momentum / (corrected_velocity.sqrt() + 1e-8);
+        }
+        fix_table_means(parameters, &means);
+
+        if epoch % 50 == 0 || epoch == epochs {
+            println!("Epoch {epoch}: error = {:.6}", mean_squared_error(entries, parameters, k));
+        }
+    }
+}
+
+fn format_table(name: &str, values: &[i32; 64]) -> String {
+    let mut result = format!("pub const {name}: [i32; 64] = [\n");
+    for rank in values.chunks(8) {
+        result += "    ";
+        result += &rank.iter().map(|value| format!("{value:>4},")).collect::<String>();
+        result += "\n";
+    }
+    result + "];\n"
+}
+
+pub fn write_tables(parameters: &[f64], path: &str) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    let mut tables = Vec::new();
+
+    for (table, name) in TABLE_NAMES.iter().enumerate() {
+        let mut white = [0; 64];
+        let mut black = [0; 64];
+        for square in 0..64 {
+            white[square] = parameters[PIECE_SQUARE_OFFSET + table * 64 + square].round() as i32;
+            black[square ^ 56] = white[square];
+        }
+        tables.push(format_table(&format!("WHITE_{name}"), &white));
+        tables.push(format_table(&format!("BLACK_{name}"), &black));
+    }
+
+    // A drop-in replacement for src/piece_square_tables.rs
+    writeln!(file, "//! Piece-square tables of the evaluation, as written by the tune subcommand.\n")?;
+    file.write_all(tables.join("\n").as_bytes())
+}
+
+pub fn tune(arguments: &[String]) {
+    if arguments.is_empty() {
+        println!("Usage: tune <dataset> [epochs] [output]");
+        return;
+    }
+    let epochs = arguments.get(1).and_then(|epochs| epochs.parse().ok()).unwrap_or(DEFAULT_EPOCHS);
+    let output = arguments.get(2).map(|output| output.as_str()).unwrap_or(DEFAULT_OUTPUT);
+
+    let entries = load_dataset(&arguments[0]);
+    if entries.is_empty() {
+        println!("No positions loaded");
+        return;
+    }
+    println!("Loaded {} positions", entries.len());
+
+    let mut parameters = initial_parameters();
+    let k = find_k(&entries, &parameters);
+    println!("K = {k:.3}, error = {:.6}", mean_squared_error(&entries, &parameters, k));
+    optimize(&entries, &mut parameters, k, epochs);
+
+    println!(
+        "PIECE_VALUES: [0, {}, 0]",
+        (0..5).map(|piece| format!("{:.0}", parameters[MATERIAL_OFFSET + piece])).collect::<Vec<String>>().join(", ")
+    );
+    // Only the piece-square tables are written, these scales have to be carried over to the terms by hand
+    for (term, name) in TERM_NAMES.iter().skip(2).enumerate() {
+        println!(
+            "{name}: mg {:.0}%, eg {:.0}% of the current values",
+            parameters[TERM_WEIGHT_OFFSET + term * 2],
+            parameters[TERM_WEIGHT_OFFSET + term * 2 + 1]
+        );
+    }
+
+    match write_tables(&parameters, output) {
+        Ok(()) => println!("Piece-square tables written to {output}"),
+        Err(error) => println!("Could not write {output}: {error}"),
+    }
+}
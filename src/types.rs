@@ -1,11 +1,264 @@
-use crate::board::Board;
+//! The small value types shared by the whole engine: squares, bitboards, pieces, moves and scores.
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr};
 
-use std::collections::HashMap;
+/// A square of the board, named like in algebraic notation
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Square {
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+/// A column of the board, from the a-file to the h-file
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum File {
+    A, B, C, D, E, F, G, H,
+}
+
+/// A row of the board, from White's first rank to the eighth
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    R1, R2, R3, R4, R5, R6, R7, R8,
+}
+
+impl Square {
+    /// Every square, in index order
+    pub const ALL: [Square; 64] = [
+        Square::A1, Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1, Square::H1,
+        Square::A2, Square::B2, Square::C2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2,
+        Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3,
+        Square::A4, Square::B4, Square::C4, Square::D4, Square::E4, Square::F4, Square::G4, Square::H4,
+        Square::A5, Square::B5, Square::C5, Square::D5, Square::E5, Square::F5, Square::G5, Square::H5,
+        Square::A6, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6,
+        Square::A7, Square::B7, Square::C7, Square::D7, Square::E7, Square::F7, Square::G7, Square::H7,
+        Square::A8, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8, Square::H8,
+    ];
+
+    /// Squares are numbered from a1 = 0, b1 = 1 up to h8 = 63
+    pub const fn new(index: usize) -> Self {
+        Square::ALL[index]
+    }
+
+    /// The square where the file and the rank cross
+    pub const fn from_file_rank(file: File, rank: Rank) -> Self {
+        Square::new(rank as usize * 8 + file as usize)
+    }
+
+    /// The file the square is on
+    pub const fn file(self) -> File {
+        File::ALL[self as usize % 8]
+    }
 
-pub type Square = usize;
+    /// The rank the square is on
+    pub const fn rank(self) -> Rank {
+        Rank::ALL[self as usize / 8]
+    }
+
+    /// The set holding only this square
+    pub const fn bitboard(self) -> Bitboard {
+        Bitboard(1 << self as u8)
+    }
+
+    /// The same square seen from the other side of the board, a1 becomes a8
+    pub const fn flip(self) -> Self {
+        Square::new(self as usize ^ 56)
+    }
+
+    /// The square `delta` steps further in square order, 8 steps being one rank up
+    pub const fn offset(self, delta: i32) -> Self {
+        Square::new((self as i32 + delta) as usize)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}{}", (b'a' + self.file() as u8) as char, self.rank() as u8 + 1)
+    }
+}
+
+impl std::str::FromStr for Square {
+    type Err = String;
+
+    /// A square name like e4
+    fn from_str(string: &str) -> Result<Self, String> {
+        match string.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Ok(Square::new(((rank - b'1') * 8 + file - b'a') as usize)),
+            _ => Err(format!("Invalid square: {string}")),
+        }
+    }
+}
+
+impl File {
+    /// Every file, from a to h
+    pub const ALL: [File; 8] = [File::A, File::B, File::C, File::D, File::E, File::F, File::G, File::H];
+
+    /// The eight squares of the file
+    pub const fn bitboard(self) -> Bitboard {
+        Bitboard(0x0101010101010101 << self as u8)
+    }
+}
+
+impl Rank {
+    /// Every rank, from the first to the eighth
+    pub const ALL: [Rank; 8] = [Rank::R1, Rank::R2, Rank::R3, Rank::R4, Rank::R5, Rank::R6, Rank::R7, Rank::R8];
+
+    /// The eight squares of the rank
+    pub const fn bitboard(self) -> Bitboard {
+        Bitboard(0xff << (self as u8 * 8))
+    }
+
+    /// The rank as seen by `color`, the eighth rank of Black is the first rank of White
+    pub const fn relative(self, color: Color) -> Self {
+        match color {
+            Color::Black => Rank::ALL[7 - self as usize],
+            _ => self,
+        }
+    }
+}
+
+/// A set of squares, bit n standing for the square with index n. Iterating over it yields
+/// the squares from a1 towards h8.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    /// The set without any square
+    pub const EMPTY: Bitboard = Bitboard(0);
+
+    /// Whether the set holds no square
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The number of squares in the set
+    pub const fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Whether the square is in the set
+    pub const fn contains(self, square: Square) -> bool {
+        self.0 & 1 << square as u8 != 0
+    }
+
+    /// The lowest square in the set, which must not be empty
+    pub const fn lsb(self) -> Square {
+        Square::new(self.0.trailing_zeros() as usize)
+    }
+
+    /// The highest square in the set, which must not be empty
+    pub const fn msb(self) -> Square {
+        Square::new(63 - self.0.leading_zeros() as usize)
+    }
+
+    /// Removes the lowest square from the set and returns it, the set must not be empty
+    pub fn pop_lsb(&mut self) -> Square {
+        let square = self.lsb();
+        self.0 &= self.0 - 1;
+        square
+    }
+}
+
+/// Wraps a generated table of raw bitboards
+pub const fn bitboards<const N: usize>(values: [u64; N]) -> [Bitboard; N] {
+    let mut result = [Bitboard::EMPTY; N];
+    let mut i = 0;
+    while i < N {
+        result[i] = Bitboard(values[i]);
+        i += 1;
+    }
+    result
+}
+
+impl Iterator for Bitboard {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.pop_lsb())
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.count() as usize, Some(self.count() as usize))
+    }
+}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Self {
+        square.bitboard()
+    }
+}
+
+macro_rules! bitboard_operator {
+    ($trait:ident, $function:ident, $assign_trait:ident, $assign_function:ident, $operator:tt) => {
+        impl $trait for Bitboard {
+            type Output = Bitboard;
+
+            fn $function(self, other: Bitboard) -> Bitboard {
+                Bitboard(self.0 $operator other.0)
+            }
+        }
+
+        impl $assign_trait for Bitboard {
+            fn $assign_function(&mut self, other: Bitboard) {
+                self.0 = self.0 $operator other.0;
+            }
+        }
+    };
+}
+
+bitboard_operator!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+bitboard_operator!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+bitboard_operator!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+impl Shl<u32> for Bitboard {
+    type Output = Bitboard;
+
+    fn shl(self, shift: u32) -> Bitboard {
+        Bitboard(self.0 << shift)
+    }
+}
+
+impl Shr<u32> for Bitboard {
+    type Output = Bitboard;
+
+    fn shr(self, shift: u32) -> Bitboard {
+        Bitboard(self.0 >> shift)
+    }
+}
+
+/// The castling rights, ((White king side, White queen side), (Black king side, Black queen side))
 pub type Castling = ((bool, bool), (bool, bool));
+/// A halfmove or fullmove counter
 pub type Clock = u8;
 
+/// What make_move saves to undo a move: the en passant square, castling rights and halfmove clock
+/// from before the move, the captured piece and the pawn taken en passant with its square
+//                     EP              Castling  HM     Captured   EP Capture
+pub type MoveHistory = (Option<Square>, Castling, Clock, Piece,     Option<(Square, Piece)>);
+
+/// The side a piece belongs to, `Empty` for an empty square
+#[allow(missing_docs)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Color {
     Empty,
@@ -13,6 +266,8 @@ pub enum Color {
     Black,
 }
 
+/// The kind of a piece, `Empty` for an empty square
+#[allow(missing_docs)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PieceType {
     Empty,
@@ -24,32 +279,293 @@ pub enum PieceType {
     King,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub struct Move {
-    pub start_square: Square,
-    pub end_square:   Square,
-    pub promotion:    PieceType,
+/// Which pseudo-legal moves to generate. Captures and Quiets split All between them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GenType {
+    /// Every pseudo-legal move
+    All,
+    /// Captures, en passant and promotions, even those that capture nothing
+    Captures,
+    /// The moves that neither capture nor promote, castling included
+    Quiets,
+    /// The moves that can get the side to move out of check, or every move when it is not in check
+    Evasions,
+    /// The quiet moves that give check
+    QuietChecks,
 }
 
+/// A move packed into 16 bits: the start square in bits 0..6, the end square in bits 6..12 and
+/// the flags in bits 12..16. Castling is the king moving two squares.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move(u16);
+
 impl Move {
-    pub fn new(start_square: Square, end_square: Square, promotion: PieceType) -> Self {
-        Move {
-            start_square: start_square,
-            end_square: end_square,
-            promotion: promotion,
+    /// A move that neither captures, castles nor promotes
+    pub const QUIET: u16 = 0b0000;
+    /// The king moving two squares towards a rook
+    pub const CASTLE: u16 = 0b0001;
+    /// Set for every capture, en passant and promotions that capture included
+    pub const CAPTURE: u16 = 0b0100;
+    /// A pawn capturing the pawn that just passed it with a double push
+    pub const EN_PASSANT: u16 = 0b0110;
+    /// Set for every promotion, the two low bits give the piece, see promotion_flags
+    pub const PROMOTION: u16 = 0b1000;
+
+    const PROMOTION_PIECES: [PieceType; 4] = [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen];
+
+    /// A move with the given flags, which are not checked against any position
+    pub const fn new(start_square: Square, end_square: Square, flags: u16) -> Self {
+        Move(start_square as u16 | (end_square as u16) << 6 | flags << 12)
+    }
+
+    /// The flags of a promotion to `piece`, without the capture flag
+    pub const fn promotion_flags(piece: PieceType) -> u16 {
+        Move::PROMOTION | match piece {
+            PieceType::Knight => 0,
+            PieceType::Bishop => 1,
+            PieceType::Rook   => 2,
+            _ => 3,
         }
     }
+
+    /// The move packed by bits
+    pub const fn from_bits(bits: u16) -> Self {
+        Move(bits)
+    }
+
+    /// The 16 bits the move is packed into
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// The square the piece moves from, the king's for castling
+    pub const fn start_square(self) -> Square {
+        Square::new((self.0 & 63) as usize)
+    }
+
+    /// The square the piece moves to, the king's for castling
+    pub const fn end_square(self) -> Square {
+        Square::new((self.0 >> 6 & 63) as usize)
+    }
+
+    /// The four flag bits, a combination of the constants above
+    pub const fn flags(self) -> u16 {
+        self.0 >> 12
+    }
+
+    /// Whether the move takes a piece, en passant included
+    pub const fn is_capture(self) -> bool {
+        self.flags() & Move::CAPTURE != 0
+    }
+
+    /// Whether the move is an en passant capture
+    pub const fn is_en_passant(self) -> bool {
+        self.flags() == Move::EN_PASSANT
+    }
+
+    /// Whether the move castles
+    pub const fn is_castle(self) -> bool {
+        self.flags() == Move::CASTLE
+    }
+
+    /// Whether the move promotes a pawn
+    pub const fn is_promotion(self) -> bool {
+        self.flags() & Move::PROMOTION != 0
+    }
+
+    /// `Empty` unless the move promotes
+    pub const fn promotion(self) -> PieceType {
+        if self.is_promotion() {
+            Move::PROMOTION_PIECES[(self.flags() & 3) as usize]
+        } else {
+            PieceType::Empty
+        }
+    }
+}
+
+/// Long algebraic notation as used by UCI, e.g. e2e4 or e7e8q
+impl fmt::Display for Move {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let promotion = match self.promotion() {
+            PieceType::Rook   => "r",
+            PieceType::Knight => "n",
+            PieceType::Bishop => "b",
+            PieceType::Queen  => "q",
+            _ => "",
+        };
+        write!(formatter, "{}{}{}", self.start_square(), self.end_square(), promotion)
+    }
+}
+
+impl fmt::Debug for Move {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Move({self}, flags {:04b})", self.flags())
+    }
+}
+
+/// The moves of a position, kept on the stack instead of the heap
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MoveList::CAPACITY],
+    len: usize,
 }
 
+impl MoveList {
+    /// The richest known position has 218 legal moves, this leaves room for the pseudo-legal moves that
+    /// leave the king in check. Only artificial positions, for example with more than nine queens a side,
+    /// come close to it
+    pub const CAPACITY: usize = 256;
+
+    /// An empty list
+    pub const fn new() -> Self {
+        MoveList { moves: [Move(0); MoveList::CAPACITY], len: 0 }
+    }
+
+    /// Adds a move at the end of the list, panics in release builds too if the list is already full
+    pub fn push(&mut self, mov: Move) {
+        assert!(self.len < MoveList::CAPACITY, "More than {} moves generated in one position", MoveList::CAPACITY);
+        self.moves[self.len] = mov;
+        self.len += 1;
+    }
+
+    /// Keeps the moves for which `keep` is true, in their order
+    pub fn retain(&mut self, mut keep: impl FnMut(&Move) -> bool) {
+        let mut kept = 0;
+        for index in 0..self.len {
+            let mov = self.moves[index];
+            if keep(&mov) {
+                self.moves[kept] = mov;
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::ops::Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl std::ops::DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl IntoIterator for MoveList {
+    type Item = Move;
+    type IntoIter = std::iter::Take<std::array::IntoIter<Move, { MoveList::CAPACITY }>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.moves.into_iter().take(self.len)
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// The piece on a square, both fields `Empty` for an empty square
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Piece {
+    /// The kind of piece
     pub typ: PieceType,
+    /// The side it belongs to
     pub color: Color,
 }
 
+/// How a stored score relates to the real score of the position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is exact, it lay between alpha and beta
+    Exact,
+    /// The score is at least this much, the search failed high
+    Lower,
+    /// The score is at most this much, no move raised alpha
+    Upper,
+}
+
+/// The result of searching a position, as kept in the transposition table
 pub struct TranspositionTableContent {
-    pub result: Vec<(Move, i32)>,
+    /// The best move found, or the move that caused the cutoff
+    pub best_move: Move,
+    /// The score from the side to move's point of view, see bound
+    pub score: i32,
+    /// The depth the position was searched to
     pub depth: i32,
+    /// Whether the score is exact or a bound
+    pub bound: Bound,
 }
 
-pub type TranspositionTable = HashMap<Board, TranspositionTableContent>;
\ No newline at end of file
+/// A pair of middlegame and endgame values, blended by game phase when evaluating
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    /// The middlegame value
+    pub mg: i32,
+    /// The endgame value
+    pub eg: i32,
+}
+
+impl Score {
+    /// A score from its middlegame and endgame values
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Score { mg, eg }
+    }
+}
+
+impl std::ops::Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl std::ops::Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        Score::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl std::ops::Mul<i32> for Score {
+    type Output = Score;
+
+    fn mul(self, factor: i32) -> Score {
+        Score::new(self.mg * factor, self.eg * factor)
+    }
+}
+
+impl std::ops::AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::SubAssign for Score {
+    fn sub_assign(&mut self, other: Score) {
+        *self = *self - other;
+    }
+}
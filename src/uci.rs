@@ -0,0 +1,124 @@
+//! The UCI protocol loop of the engine binary.
+use crate::{board, consts, evaluation, limits, options, perft, search, bench};
+
+use std::sync::Arc;
+
+// The running search and its control, the search prints its own bestmove when done
+type RunningSearch = (tokio::task::JoinHandle<()>, Arc<search::SearchControl>);
+
+fn start_search(limits: limits::SearchLimits, board: &board::Board, options: &options::SearchOptions) -> RunningSearch {
+    let mut board = board.clone();
+    let options = options.clone();
+    let control = Arc::new(search::SearchControl::new(limits.ponder));
+    let search_control = control.clone();
+    let handle = tokio::spawn(async move {
+        println!("{}", search::search(&limits, &mut board, &options, &search_control).await.to_uci());
+    });
+    (handle, control)
+}
+
+async fn stop_search(running_search: &mut Option<RunningSearch>) {
+    if let Some((handle, control)) = running_search.take() {
+        control.stop();
+        handle.await.unwrap();
+    }
+}
+
+/// Reads UCI commands from standard input until "quit" or the end of the input, then waits for a running search
+/// to print its bestmove
+pub async fn run() {
+    let mut board: board::Board = consts::EMPTY_BOARD;
+    let mut options = options::SearchOptions::default();
+    board.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
+
+    // Input is read on its own thread, so that commands like stop and ponderhit reach a running search
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            if sender.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    let mut running_search: Option<RunningSearch> = None;
+
+    while let Some(line) = receiver.recv().await {
+        let command = line.split(" ").collect::<Vec<&str>>();
+        if running_search.as_ref().is_some_and(|(handle, _)| handle.is_finished()) {
+            running_search = None;
+        }
+        match command[0].replace("\n", "").replace("\r", "").as_str() {
+            "isready" => println!("readyok"),
+            "uci" => {
+                println!("id name chess_v3");
+                println!("id author Huggepugge1");
+                options.print_uci_options();
+                println!("uciok");
+            },
+            "quit" => break,
+            "stop" => stop_search(&mut running_search).await,
+            "ponderhit" => {
+                if let Some((_, control)) = &running_search {
+                    control.ponderhit();
+                }
+            },
+            "printboard" => board.print_board(),
+            "eval" => evaluation::print_trace(&board),
+            "bench" => {
+                stop_search(&mut running_search).await;
+                bench::bench(&command[1..].iter().map(|argument| argument.trim().to_string()).collect::<Vec<String>>(), &options).await;
+            },
+            "ucinewgame" => options.transposition_table.clear(),
+            "setoption" => options.set_option(&command[1..].iter().map(|argument| argument.trim()).collect::<Vec<&str>>()),
+            "position" => {
+                let arguments = command[1..].iter().map(|argument| argument.trim()).filter(|argument| !argument.is_empty()).collect::<Vec<&str>>();
+                let moves_position = arguments.iter().position(|argument| *argument == "moves").unwrap_or(arguments.len());
+                match arguments.first() {
+                    None => println!("position requires at least 1 argument!"),
+                    Some(&"startpos") => board.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string()),
+                    Some(&"fen") => board.load_fen(arguments[1..moves_position].join(" ")),
+                    // A bare FEN without the "fen" keyword
+                    Some(_) => board.load_fen(arguments[..moves_position].join(" ")),
+                }
+
+                for mov in arguments.iter().skip(moves_position + 1) {
+                    match board.string_to_move(mov) {
+                        Some(mov) => board.make_move(mov),
+                        None => {
+                            println!("Invalid move: {mov}");
+                            break;
+                        },
+                    }
+                }
+            },
+            "go" => {
+                stop_search(&mut running_search).await;
+                match command.get(1).map(|argument| argument.trim()) {
+                    Some("perft") => {
+                        if command.len() == 3 {
+                            match command[2].trim().parse::<i32>() {
+                                Ok(depth) => {
+                                    perft::perft(depth, &board, options.threads, options.perft_table.as_deref());
+                                },
+                                Err(_) => println!("info string Invalid perft depth: {}", command[2].trim()),
+                            }
+                        } else {
+                            println!("\"go perft\" needs **ONE** argument");
+                        }
+                    },
+                    Some("abtest") => {
+                        search::alpha_beta_test(&mut board, &options).await;
+                    },
+                    _ => match limits::SearchLimits::parse(&command[1..], &board) {
+                        Ok(limits) => running_search = Some(start_search(limits, &board, &options)),
+                        Err(error) => println!("{error}"),
+                    },
+                }
+            },
+            x => println!("{x} is either not implemented or not a valid UCI command"),
+        }
+    }
+
+    // Also reached when the input ends, so a search that was still running answers before the engine exits
+    stop_search(&mut running_search).await;
+}
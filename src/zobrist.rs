@@ -0,0 +1,81 @@
+//! Zobrist keys of the pieces, castling rights, en passant files and side to move, for hashing positions.
+
+use crate::board::Board;
+use crate::types::*;
+
+const fn generate_keys<const N: usize>(mut seed: u64) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut i = 0;
+    while i < N {
+        seed ^= seed >> 12;
+        seed ^= seed << 25;
+        seed ^= seed >> 27;
+        keys[i] = seed.wrapping_mul(0x2545f4914f6cdd1d);
+        i += 1;
+    }
+    keys
+}
+
+pub const PIECE_KEYS: [u64; 768] = generate_keys(0x9e3779b97f4a7c15);
+//                                    K, Q, k, q
+pub const CASTLING_KEYS: [u64; 4] = generate_keys(0xd1b54a32d192ed03);
+/// Indexed by the file of the en passant square
+pub const EN_PASSANT_KEYS: [u64; 8] = generate_keys(0x8bb84b93962eacc9);
+pub const SIDE_KEY: u64 = generate_keys::<1>(0x4f1bbcdcbfa53e0b)[0];
+
+pub fn piece_key(piece: &Piece, square: Square) -> u64 {
+    let color = match piece.color {
+        Color::White => 0,
+        Color::Black => 6,
+        Color::Empty => return 0,
+    };
+    let typ = match piece.typ {
+        PieceType::Pawn   => 0,
+        PieceType::Rook   => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Queen  => 4,
+        PieceType::King   => 5,
+        PieceType::Empty  => return 0,
+    };
+    PIECE_KEYS[(color + typ) * 64 + square as usize]
+}
+
+impl Board {
+    /// A hash of the position, equal for positions that only differ in their history.
+    pub fn zobrist_key(&self) -> u64 {
+        let mut key = 0;
+        for square in self.white_pieces | self.black_pieces {
+            key ^= piece_key(&self.get_piece(square), square);
+        }
+
+        if self.turn == Color::Black {
+            key ^= SIDE_KEY;
+        }
+
+        let ((white_king_side, white_queen_side), (black_king_side, black_queen_side)) = self.castling_rights;
+        for (right, castling_key) in [white_king_side, white_queen_side, black_king_side, black_queen_side].iter().zip(CASTLING_KEYS) {
+            if *right {
+                key ^= castling_key;
+            }
+        }
+
+        if let Some(square) = self.en_passant {
+            key ^= EN_PASSANT_KEYS[square.file() as usize];
+        }
+
+        key
+    }
+
+    /// A hash of the pawns alone, read straight from the bitboards as it is probed on every evaluation.
+    pub fn pawn_key(&self) -> u64 {
+        let mut key = 0;
+        for square in self.pawns & self.white_pieces {
+            key ^= PIECE_KEYS[square as usize];
+        }
+        for square in self.pawns & self.black_pieces {
+            key ^= PIECE_KEYS[6 * 64 + square as usize];
+        }
+        key
+    }
+}
@@ -0,0 +1,27 @@
+// The bench signature: the same moves, scores and node counts on every run
+use std::process::Command;
+
+// Every line but the timings
+fn bench(depth: i32) -> Vec<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_chess_v3"))
+        .args(["bench", &depth.to_string()])
+        .output()
+        .expect("Could not start the engine");
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.starts_with("Total time") && !line.starts_with("Nodes/second"))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[test]
+fn signature_repeats() {
+    let first = bench(3);
+    let second = bench(3);
+
+    let signature = first.iter().find_map(|line| line.strip_prefix("Nodes searched  : ")).expect("No node count");
+    assert!(signature.parse::<u64>().unwrap() > 0);
+    assert!(first.iter().filter(|line| line.starts_with("Position ")).count() > 1);
+    assert_eq!(first, second);
+}
@@ -0,0 +1,95 @@
+// Parsing the arguments of "go" into search limits, and the time a search gets from them
+use chess_v3::board::Board;
+use chess_v3::consts::EMPTY_BOARD;
+use chess_v3::limits::SearchLimits;
+use chess_v3::types::*;
+
+const START_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+fn load(fen: &str) -> Board {
+    let mut board = EMPTY_BOARD;
+    board.load_fen(fen.to_string());
+    board
+}
+
+fn parse(arguments: &str) -> Result<SearchLimits, String> {
+    SearchLimits::parse(&arguments.split(' ').collect::<Vec<&str>>(), &load(START_POSITION))
+}
+
+#[test]
+fn every_limit() {
+    let limits = parse("depth 5 nodes 1000 movetime 200 mate 3 wtime 60000 btime 50000 winc 1000 binc 500 movestogo 20").unwrap();
+
+    assert_eq!(limits.depth, Some(5));
+    assert_eq!(limits.nodes, Some(1000));
+    assert_eq!(limits.movetime, Some(200));
+    assert_eq!(limits.mate, Some(3));
+    assert_eq!(limits.white_time, Some(60000));
+    assert_eq!(limits.black_time, Some(50000));
+    assert_eq!(limits.white_increment, 1000);
+    assert_eq!(limits.black_increment, 500);
+    assert_eq!(limits.moves_to_go, Some(20));
+    assert!(!limits.infinite && !limits.ponder);
+    assert!(limits.search_moves.is_empty());
+}
+
+#[test]
+fn no_limits() {
+    let limits = parse("").unwrap();
+    assert_eq!(limits.depth, None);
+    assert_eq!(limits.nodes, None);
+    assert_eq!(limits.time(Color::White), None);
+
+    let limits = parse("infinite ponder wtime 1000").unwrap();
+    assert!(limits.infinite && limits.ponder);
+    assert_eq!(limits.time(Color::White), None);
+}
+
+#[test]
+fn extra_whitespace() {
+    let limits = SearchLimits::parse(&[" depth ", "", "3\r"], &load(START_POSITION)).unwrap();
+    assert_eq!(limits.depth, Some(3));
+}
+
+#[test]
+fn search_moves_end_at_the_first_other_argument() {
+    let limits = parse("searchmoves e2e4 g1f3 depth 3").unwrap();
+    let moves = limits.search_moves.iter().map(|mov| mov.to_string()).collect::<Vec<String>>();
+
+    assert_eq!(moves, ["e2e4", "g1f3"]);
+    assert_eq!(limits.depth, Some(3));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(parse("depth").unwrap_err(), "depth needs a value");
+    assert_eq!(parse("depth three").unwrap_err(), "Invalid depth value: three");
+    assert_eq!(parse("nodes -1").unwrap_err(), "Invalid nodes value: -1");
+    assert_eq!(parse("wtime 1000 binc").unwrap_err(), "binc needs a value");
+    assert_eq!(parse("perft 3").unwrap_err(), "perft is either not implemented or not a valid argument for \"go\"");
+    // Not a move, so the end of the search moves
+    assert_eq!(parse("searchmoves e2e4 e2").unwrap_err(), "e2 is either not implemented or not a valid argument for \"go\"");
+}
+
+#[test]
+fn time_from_the_clock() {
+    // 30 moves to go when the GUI does not say, and three quarters of the increment
+    assert_eq!(parse("wtime 60000 btime 30000").unwrap().time(Color::White), Some(2000));
+    assert_eq!(parse("wtime 60000 btime 30000").unwrap().time(Color::Black), Some(1000));
+    assert_eq!(parse("wtime 60000 winc 1000").unwrap().time(Color::White), Some(2750));
+    assert_eq!(parse("wtime 60000 movestogo 10").unwrap().time(Color::White), Some(6000));
+    assert_eq!(parse("wtime 60000 movestogo 0").unwrap().time(Color::White), Some(59950));
+    // Never more than the clock minus the move overhead, and never nothing
+    assert_eq!(parse("wtime 1000 winc 5000").unwrap().time(Color::White), Some(950));
+    assert_eq!(parse("wtime 10").unwrap().time(Color::White), Some(1));
+    // Only the side to move's clock counts
+    assert_eq!(parse("btime 60000").unwrap().time(Color::White), None);
+}
+
+#[test]
+fn time_from_movetime() {
+    assert_eq!(parse("movetime 500").unwrap().time(Color::Black), Some(500));
+    // Whichever is shorter
+    assert_eq!(parse("movetime 500 wtime 60000").unwrap().time(Color::White), Some(500));
+    assert_eq!(parse("movetime 5000 wtime 60000").unwrap().time(Color::White), Some(2000));
+}
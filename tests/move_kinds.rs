@@ -0,0 +1,79 @@
+// Checks the move generation modes against the full move list, in every position two plies deep
+use chess_v3::board::Board;
+use chess_v3::consts::EMPTY_BOARD;
+use chess_v3::types::*;
+
+const POSITIONS: [&str; 9] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1",
+    "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+    "3k4/8/8/8/8/8/8/R3K3 w Q - 0 1",
+    "8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1",
+];
+
+fn sorted(moves: impl IntoIterator<Item = Move>) -> Vec<u16> {
+    let mut bits: Vec<u16> = moves.into_iter().map(|mov| mov.bits()).collect();
+    bits.sort();
+    bits
+}
+
+fn check_kinds(board: &mut Board) {
+    let all = board.generate_moves();
+    let captures = board.generate_moves_of_kind(GenType::Captures);
+    let quiets = board.generate_moves_of_kind(GenType::Quiets);
+
+    assert!(captures.iter().all(|mov| mov.is_capture() || mov.is_promotion()));
+    assert!(quiets.iter().all(|mov| !mov.is_capture() && !mov.is_promotion()));
+    assert_eq!(sorted(captures.iter().chain(&quiets).copied()), sorted(all.iter().copied()));
+    // Each kind keeps the order of the full list
+    let mut remaining = all.iter();
+    assert!(captures.iter().all(|mov| remaining.any(|other| other == mov)));
+
+    let mut checks = Vec::new();
+    for mov in &all {
+        board.make_move(*mov);
+        let gives_check = board.in_check();
+        board.unmake_move(*mov);
+        assert_eq!(board.gives_check(mov), gives_check, "{} {mov}", board.to_fen());
+        if gives_check && quiets.contains(mov) {
+            checks.push(*mov);
+        }
+    }
+    assert_eq!(sorted(board.generate_moves_of_kind(GenType::QuietChecks)), sorted(checks));
+
+    let evasions = board.generate_moves_of_kind(GenType::Evasions);
+    assert!(evasions.iter().all(|mov| all.contains(mov)));
+    if board.in_check() {
+        assert!(!board.checkers().is_empty());
+        let legal_evasions = evasions.iter().copied().filter(|mov| board.is_legal(mov));
+        assert_eq!(sorted(legal_evasions), sorted(board.generate_legal_moves()));
+    } else {
+        assert!(board.checkers().is_empty());
+        assert_eq!(sorted(evasions), sorted(all));
+    }
+}
+
+#[test]
+fn generation_modes() {
+    let mut in_check = 0;
+    for fen in POSITIONS {
+        let mut board = EMPTY_BOARD;
+        board.load_fen(fen.to_string());
+        for first in board.generate_legal_moves() {
+            board.make_move(first);
+            for second in board.generate_legal_moves() {
+                board.make_move(second);
+                in_check += board.in_check() as usize;
+                check_kinds(&mut board);
+                board.unmake_move(second);
+            }
+            check_kinds(&mut board);
+            board.unmake_move(first);
+        }
+    }
+    assert!(in_check > 100);
+}
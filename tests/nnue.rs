@@ -0,0 +1,106 @@
+// Checks the incrementally updated accumulators against ones built from scratch, run with: cargo test --features nnue
+#![cfg(feature = "nnue")]
+use chess_v3::board::Board;
+use chess_v3::consts::EMPTY_BOARD;
+use chess_v3::nnue::{self, Network};
+use std::sync::Arc;
+
+const HIDDEN_SIZE: usize = 32;
+
+// Captures, en passant, castling on both sides, promotions with and without capture and king moves
+const POSITIONS: [&str; 4] = [
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+];
+
+// Small random weights, so that few accumulator values reach the clipping bounds and hide a difference
+fn write_network(feature_set: u32, piece_kinds: usize) -> Arc<Network> {
+    let mut seed = 0x2545f4914f6cdd1d_u64 ^ feature_set as u64;
+    let mut random = |range: i64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % (2 * range as u64 + 1)) as i64 - range
+    };
+
+    let mut bytes = b"CHV3NNUE".to_vec();
+    bytes.extend(feature_set.to_le_bytes());
+    bytes.extend((HIDDEN_SIZE as u32).to_le_bytes());
+    for _ in 0..64 * piece_kinds * 64 * HIDDEN_SIZE {
+        bytes.extend((random(4) as i16).to_le_bytes());
+    }
+    for _ in 0..HIDDEN_SIZE {
+        bytes.extend((random(32) as i16 + 96).to_le_bytes());
+    }
+    for _ in 0..2 * HIDDEN_SIZE {
+        bytes.extend((random(32) as i8).to_le_bytes());
+    }
+    bytes.extend(0_i32.to_le_bytes());
+    load(&format!("{feature_set}"), bytes)
+}
+
+fn load(name: &str, bytes: Vec<u8>) -> Arc<Network> {
+    let path = std::env::temp_dir().join(format!("chess_v3_test_{}_{name}.nnue", std::process::id()));
+    std::fs::write(&path, bytes).unwrap();
+    let network = Network::load(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    Arc::new(network)
+}
+
+fn refreshed(board: &Board, network: &Arc<Network>) -> i32 {
+    let mut fresh = EMPTY_BOARD;
+    fresh.load_fen(board.to_fen());
+    fresh.enable_nnue(network.clone());
+    nnue::evaluate(&fresh)
+}
+
+fn check(board: &Board, network: &Arc<Network>) {
+    assert_eq!(nnue::evaluate(board), refreshed(board, network), "{}", board.to_fen());
+}
+
+#[test]
+fn incremental_updates_match_refresh() {
+    for (feature_set, piece_kinds) in [(0, 10), (1, 12)] {
+        let network = write_network(feature_set, piece_kinds);
+        for fen in POSITIONS {
+            let mut board = EMPTY_BOARD;
+            board.load_fen(fen.to_string());
+            board.enable_nnue(network.clone());
+            let root = nnue::evaluate(&board);
+
+            for first in board.generate_legal_moves() {
+                board.make_move(first);
+                check(&board, &network);
+                for second in board.generate_legal_moves() {
+                    board.make_move(second);
+                    check(&board, &network);
+                    board.make_null_move();
+                    check(&board, &network);
+                    board.unmake_null_move();
+                    board.unmake_move(second);
+                }
+                check(&board, &network);
+                board.unmake_move(first);
+            }
+            assert_eq!(nnue::evaluate(&board), root);
+        }
+    }
+}
+
+#[test]
+fn output_scaling_does_not_overflow() {
+    // Only the output bias, large enough that scaling it by 400 overflows i32
+    let mut bytes = b"CHV3NNUE".to_vec();
+    bytes.extend(0_u32.to_le_bytes());
+    bytes.extend((HIDDEN_SIZE as u32).to_le_bytes());
+    bytes.extend(vec![0; (64 * 10 * 64 * HIDDEN_SIZE + HIDDEN_SIZE) * 2 + 2 * HIDDEN_SIZE]);
+    bytes.extend(16_320_000_i32.to_le_bytes());
+    let network = load("bias", bytes);
+
+    let mut board = EMPTY_BOARD;
+    board.load_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
+    board.enable_nnue(network);
+    assert_eq!(nnue::evaluate(&board), 400_000);
+}
@@ -0,0 +1,122 @@
+// Runs the engine's "go perft" against the published node counts. Only the shallow depths run by default,
+// the full suite with: cargo test --release --test perft -- --ignored
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Depths with more leaf nodes than this are left to the full suite
+const FAST_NODE_LIMIT: u64 = 100_000;
+// The edge cases only come with one deep count each, and have few enough pieces to run most of them
+const EDGE_CASE_NODE_LIMIT: u64 = 2_000_000;
+
+// Name, FEN and the node counts from depth 1 onwards
+const STANDARD_POSITIONS: [(&str, &str, &[u64]); 6] = [
+    ("startpos", "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[20, 400, 8_902, 197_281, 4_865_609, 119_060_324]),
+    ("kiwipete", "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", &[48, 2_039, 97_862, 4_085_603, 193_690_690]),
+    ("position 3", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", &[14, 191, 2_812, 43_238, 674_624, 11_030_083, 178_633_661]),
+    ("position 4", "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", &[6, 264, 9_467, 422_333, 15_833_292]),
+    ("position 5", "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", &[44, 1_486, 62_379, 2_103_487, 89_941_194]),
+    ("position 6", "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", &[46, 2_079, 89_890, 3_894_594, 164_075_551]),
+];
+
+// Only the deepest count of each is published, the rest are zero and skipped
+const EDGE_CASES: [(&str, &str, &[u64]); 14] = [
+    ("illegal en passant", "3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1", &[0, 0, 0, 0, 0, 1_134_888]),
+    ("en passant gives check", "8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1", &[0, 0, 0, 0, 0, 1_015_133]),
+    ("en passant pinned", "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1", &[0, 0, 0, 0, 0, 1_440_467]),
+    ("short castling gives check", "5k2/8/8/8/8/8/8/4K2R w K - 0 1", &[0, 0, 0, 0, 0, 661_072]),
+    ("long castling gives check", "3k4/8/8/8/8/8/8/R3K3 w Q - 0 1", &[0, 0, 0, 0, 0, 803_711]),
+    ("castling rights", "r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1", &[0, 0, 0, 1_274_206]),
+    ("castling prevented", "r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1", &[0, 0, 0, 1_720_476]),
+    ("promotion out of check", "2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1", &[0, 0, 0, 0, 0, 3_821_001]),
+    ("discovered check", "8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1", &[0, 0, 0, 0, 1_004_658]),
+    ("promotion gives check", "4k3/1P6/8/8/8/8/K7/8 w - - 0 1", &[0, 0, 0, 0, 0, 217_342]),
+    ("underpromotion gives check", "8/P1k5/K7/8/8/8/8/8 w - - 0 1", &[0, 0, 0, 0, 0, 92_683]),
+    ("self stalemate", "K1k5/8/P7/8/8/8/8/8 w - - 0 1", &[0, 0, 0, 0, 0, 2_217]),
+    ("stalemate and checkmate", "8/k1P5/8/1K6/8/8/8/8 w - - 0 1", &[0, 0, 0, 0, 0, 0, 567_584]),
+    ("stalemate and checkmate 2", "8/8/2k5/5q2/5n2/8/5K2/8 b - - 0 1", &[0, 0, 0, 23_527]),
+];
+
+// Feeds the commands to one engine process and returns everything it printed
+fn run_engine(input: &str) -> String {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_chess_v3"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Could not start the engine");
+
+    engine.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    String::from_utf8(engine.wait_with_output().unwrap().stdout).unwrap()
+}
+
+// Runs "go perft" for each depth in one engine process and returns the totals
+fn perft(fen: &str, depths: &[usize]) -> Vec<u64> {
+    let mut input = format!("position fen {fen}\n");
+    for depth in depths {
+        input += &format!("go perft {depth}\n");
+    }
+    input += "quit\n";
+
+    run_engine(&input)
+        .lines()
+        .filter_map(|line| line.strip_prefix("Nodes searched: "))
+        .map(|nodes| nodes.trim().parse().unwrap())
+        .collect()
+}
+
+fn check(positions: &[(&str, &str, &[u64])], node_limit: u64) {
+    for (name, fen, counts) in positions {
+        let depths = (1..=counts.len())
+            .filter(|depth| counts[depth - 1] != 0 && counts[depth - 1] <= node_limit)
+            .collect::<Vec<usize>>();
+        if depths.is_empty() {
+            continue;
+        }
+
+        let results = perft(fen, &depths);
+        assert_eq!(results.len(), depths.len(), "{name}: the engine did not answer every perft");
+        for (depth, result) in depths.iter().zip(results) {
+            assert_eq!(result, counts[depth - 1], "{name} ({fen}) at depth {depth}");
+        }
+    }
+}
+
+#[test]
+fn standard_positions() {
+    check(&STANDARD_POSITIONS, FAST_NODE_LIMIT);
+}
+
+#[test]
+fn edge_cases() {
+    check(&EDGE_CASES, EDGE_CASE_NODE_LIMIT);
+}
+
+#[test]
+fn perft_hash() {
+    // Counted without a table, then twice with one, the second time from what the first stored
+    let kiwipete = STANDARD_POSITIONS[1].1;
+    for hash in ["0", "1"] {
+        let output = run_engine(&format!("setoption name PerftHash value {hash}\nposition fen {kiwipete}\ngo perft 3\ngo perft 3\nquit\n"));
+        let counts = output.lines().filter(|line| line.starts_with("Nodes searched: ")).collect::<Vec<&str>>();
+        assert_eq!(counts, ["Nodes searched: 97862"; 2], "PerftHash {hash}");
+    }
+}
+
+#[test]
+fn invalid_depth() {
+    // Reported instead of ending the engine, which still answers the next perft
+    let output = run_engine("go perft two\ngo perft 2\nquit\n");
+    assert!(output.contains("info string Invalid perft depth: two"), "{output}");
+    assert!(output.contains("Nodes searched: 400"), "{output}");
+}
+
+#[test]
+#[ignore]
+fn standard_positions_full() {
+    check(&STANDARD_POSITIONS, u64::MAX);
+}
+
+#[test]
+#[ignore]
+fn edge_cases_full() {
+    check(&EDGE_CASES, u64::MAX);
+}
@@ -0,0 +1,48 @@
+// Runs perftdiff against chess_v3 itself, which must agree, and against perft_stub, whose generator
+// misses a move at the end of the first line
+use std::path::PathBuf;
+use std::process::Command;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn perftdiff(engine: &str, depth: i32, fen: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_chess_v3"))
+        .args(["perftdiff", engine, &depth.to_string(), fen])
+        .output()
+        .expect("Could not start the engine");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+// The stub is an example, which cargo does not build when only this test is asked for
+fn perft_stub() -> PathBuf {
+    let mut build = Command::new(env!("CARGO"));
+    build.args(["build", "--example", "perft_stub"]);
+    if !cfg!(debug_assertions) {
+        build.arg("--release");
+    }
+    let output = build.output().expect("Could not start cargo");
+    assert!(output.status.success(), "Could not build perft_stub: {}", String::from_utf8_lossy(&output.stderr));
+
+    let binaries = PathBuf::from(env!("CARGO_BIN_EXE_chess_v3")).with_file_name("examples");
+    binaries.join(format!("perft_stub{}", std::env::consts::EXE_SUFFIX))
+}
+
+#[test]
+fn no_difference_against_itself() {
+    let output = perftdiff(env!("CARGO_BIN_EXE_chess_v3"), 3, KIWIPETE);
+    assert!(output.contains("No difference at depth 3"), "{output}");
+}
+
+#[test]
+fn finds_the_missing_move() {
+    let output = perftdiff(perft_stub().to_str().unwrap(), 3, KIWIPETE);
+    let lines = output.lines().collect::<Vec<&str>>();
+
+    // Two plies down the first line, where the stub leaves out the first move
+    let moves = lines.iter().find_map(|line| line.strip_prefix("Move lists differ after: ")).expect(&output);
+    assert_eq!(moves.split(' ').count(), 2, "{output}");
+    assert!(lines.iter().any(|line| line.starts_with("FEN: ")), "{output}");
+    assert!(lines.contains(&"Only the reference engine generates: -"), "{output}");
+    let extra = lines.iter().find_map(|line| line.strip_prefix("Only chess_v3 generates: ")).expect(&output);
+    assert_eq!(extra.split(' ').count(), 1, "{output}");
+}
@@ -0,0 +1,147 @@
+// Checks standard algebraic notation both ways: writing moves with to_san and reading them back with from_san
+use chess_v3::board::Board;
+use chess_v3::consts::EMPTY_BOARD;
+use chess_v3::types::*;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn load(fen: &str) -> Board {
+    let mut board = EMPTY_BOARD;
+    board.load_fen(fen.to_string());
+    board
+}
+
+// The legal move written as `uci` in UCI notation
+fn find_move(board: &mut Board, uci: &str) -> Move {
+    board.generate_legal_moves().into_iter().find(|mov| mov.to_string() == uci).expect("The move is legal")
+}
+
+fn san(fen: &str, uci: &str) -> String {
+    let mut board = load(fen);
+    let mov = find_move(&mut board, uci);
+    board.to_san(&mov)
+}
+
+#[test]
+fn pieces_and_pawns() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(san(start, "e2e4"), "e4");
+    assert_eq!(san(start, "g1f3"), "Nf3");
+    assert_eq!(san(KIWIPETE, "e5f7"), "Nxf7");
+    assert_eq!(san(KIWIPETE, "d5e6"), "dxe6");
+    assert_eq!(san(KIWIPETE, "g2h3"), "gxh3");
+}
+
+#[test]
+fn disambiguation() {
+    // Rooks on the same rank, told apart by file
+    let by_file = "4k3/8/8/8/8/8/8/R4RK1 w - - 0 1";
+    assert_eq!(san(by_file, "a1d1"), "Rad1");
+    assert_eq!(san(by_file, "f1d1"), "Rfd1");
+    assert_eq!(san(by_file, "a1a5"), "Ra5");
+
+    // Rooks on the same file, told apart by rank
+    let by_rank = "4k3/8/8/8/R7/8/8/R3K3 w - - 0 1";
+    assert_eq!(san(by_rank, "a1a3"), "R1a3");
+    assert_eq!(san(by_rank, "a4a3"), "R4a3");
+    assert_eq!(san(by_rank, "a4d4"), "Rd4");
+
+    // A queen sharing its file with one queen and its rank with another needs both
+    let by_both = "8/7k/8/8/8/Q7/8/Q1Q3K1 w - - 0 1";
+    assert_eq!(san(by_both, "a1b2"), "Qa1b2");
+    assert_eq!(san(by_both, "a3b2"), "Q3b2");
+    assert_eq!(san(by_both, "c1b2"), "Qcb2");
+
+    // A knight that is pinned cannot reach the square, so the other one needs no disambiguation
+    let pinned = "4k3/4r3/8/8/8/8/4N3/2N1K3 w - - 0 1";
+    assert_eq!(san(pinned, "c1d3"), "Nd3");
+}
+
+#[test]
+fn promotions() {
+    let fen = "1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(san(fen, "a7a8q"), "a8=Q");
+    assert_eq!(san(fen, "a7a8n"), "a8=N");
+    assert_eq!(san(fen, "a7b8q"), "axb8=Q+");
+    assert_eq!(san(fen, "a7b8r"), "axb8=R+");
+    assert_eq!(san(fen, "a7b8b"), "axb8=B");
+}
+
+#[test]
+fn castling() {
+    let white = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    assert_eq!(san(white, "e1g1"), "O-O");
+    assert_eq!(san(white, "e1c1"), "O-O-O");
+
+    let black = "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1";
+    assert_eq!(san(black, "e8g8"), "O-O");
+    assert_eq!(san(black, "e8c8"), "O-O-O");
+}
+
+#[test]
+fn check_and_mate() {
+    assert_eq!(san("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), "Ra8+");
+    assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+    assert_eq!(san("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2", "d8h4"), "Qh4#");
+    // Castling can give check too
+    assert_eq!(san("5k2/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1"), "O-O+");
+}
+
+#[test]
+fn reading() {
+    let mut board = load("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    let capture = find_move(&mut board, "a7b8q");
+    // Check marks and annotations are optional
+    assert_eq!(board.from_san("axb8=Q+"), Some(capture));
+    assert_eq!(board.from_san("axb8=Q"), Some(capture));
+    assert_eq!(board.from_san("axb8=Q+!?"), Some(capture));
+
+    let mut board = load("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    let castle = find_move(&mut board, "e1g1");
+    // Castling may be written with zeros
+    assert_eq!(board.from_san("O-O"), Some(castle));
+    assert_eq!(board.from_san("0-0"), Some(castle));
+    assert_eq!(board.from_san("O-O-O"), Some(find_move(&mut board, "e1c1")));
+}
+
+#[test]
+fn rejects_ambiguous_and_illegal() {
+    // Both rooks reach d1
+    let mut board = load("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1");
+    assert_eq!(board.from_san("Rd1"), None);
+    assert!(board.from_san("Rad1").is_some());
+
+    // Both knights reach d2, though neither needs the rank
+    let mut board = load("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1");
+    assert_eq!(board.from_san("Nd2"), None);
+    assert_eq!(board.from_san("N1d2"), None);
+    assert!(board.from_san("Nbd2").is_some());
+
+    let mut board = load("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    assert_eq!(board.from_san("e5"), None);
+    assert_eq!(board.from_san("Nf6"), None);
+    assert_eq!(board.from_san("Bc4"), None);
+    assert_eq!(board.from_san("O-O"), None);
+    assert_eq!(board.from_san("exd5"), None);
+    assert_eq!(board.from_san("e8=Q"), None);
+    assert_eq!(board.from_san("Qz9"), None);
+    assert_eq!(board.from_san(""), None);
+
+    // A pinned piece may not leave the pin, nor the king move into check
+    let mut board = load("4k3/4r3/8/8/8/8/4N3/2N1K3 w - - 0 1");
+    assert_eq!(board.from_san("Nf4"), None);
+    let mut board = load("4k3/8/8/8/8/8/8/r3K3 w - - 0 1");
+    assert_eq!(board.from_san("Kf1"), None);
+    assert!(board.from_san("Kf2").is_some());
+}
+
+#[test]
+fn round_trip() {
+    for fen in [KIWIPETE, "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"] {
+        let mut board = load(fen);
+        for mov in board.generate_legal_moves() {
+            let san = board.to_san(&mov);
+            assert_eq!(board.from_san(&san), Some(mov), "{san} in {fen}");
+        }
+    }
+}
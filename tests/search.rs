@@ -0,0 +1,123 @@
+// Searches through the library: reproducible results in deterministic mode, the limits a search keeps to, and
+// what the pruning, the extensions and the helper threads do to its results
+use chess_v3::board::Board;
+use chess_v3::consts::EMPTY_BOARD;
+use chess_v3::limits::SearchLimits;
+use chess_v3::options::SearchOptions;
+use chess_v3::search::{self, SearchControl, SearchResult};
+
+use std::time::{Duration, Instant};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn load(fen: &str) -> Board {
+    let mut board = EMPTY_BOARD;
+    board.load_fen(fen.to_string());
+    board
+}
+
+// Runs "go" with the arguments in the position
+async fn go(fen: &str, arguments: &[&str], options: &SearchOptions) -> SearchResult {
+    let mut board = load(fen);
+    let limits = SearchLimits::parse(arguments, &board).unwrap();
+    search::search(&limits, &mut board, options, &SearchControl::silent()).await
+}
+
+fn deterministic() -> SearchOptions {
+    // More than one thread is asked for, deterministic mode searches with one anyway
+    SearchOptions { deterministic: true, threads: 4, ..SearchOptions::default() }
+}
+
+#[tokio::test]
+async fn deterministic_node_search_repeats() {
+    let options = deterministic();
+    let first = go(KIWIPETE, &["nodes", "20000"], &options).await;
+    // The transposition table is cleared between searches, so what the first one stored makes no difference
+    let second = go(KIWIPETE, &["nodes", "20000"], &options).await;
+
+    assert_eq!(first.best_move, second.best_move);
+    assert_eq!(first.ponder_move, second.ponder_move);
+    assert_eq!(first.score, second.score);
+    assert_eq!(first.nodes, second.nodes);
+}
+
+#[tokio::test]
+async fn deterministic_depth_search_ignores_the_clock() {
+    let options = deterministic();
+    let free = go(KIWIPETE, &["depth", "4"], &options).await;
+    let timed = go(KIWIPETE, &["depth", "4", "wtime", "1", "btime", "1"], &options).await;
+
+    assert_eq!(free.best_move, timed.best_move);
+    assert_eq!(free.nodes, timed.nodes);
+}
+
+#[tokio::test]
+async fn deterministic_search_keeps_to_the_clock() {
+    // Without a depth or node limit only the clock ends the search, which would otherwise go on to the maximum depth
+    let options = deterministic();
+    let start = Instant::now();
+    let search = go(KIWIPETE, &["wtime", "1000", "btime", "1000"], &options);
+    let result = tokio::time::timeout(Duration::from_secs(5), search).await.expect("The search ignored the clock");
+
+    assert!(start.elapsed() < Duration::from_secs(1), "The search took {:?}", start.elapsed());
+    assert!(load(KIWIPETE).generate_legal_moves().contains(&result.best_move));
+}
+
+#[tokio::test]
+async fn quiescence_sees_mate() {
+    // At depth 1 the reply to Ra8 is left to the quiescence search, which must not stand pat in check
+    let result = go("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", &["depth", "1"], &SearchOptions::default()).await;
+
+    assert_eq!(result.best_move.to_string(), "a1a8");
+    assert_eq!(result.score, search::MATE - 1);
+}
+
+// Mate in two: Nf6+ gxf6 Bxf7#
+const MATE_IN_TWO: &str = "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1";
+
+#[tokio::test]
+async fn pruning_keeps_the_mate_and_saves_nodes() {
+    let pruned = deterministic();
+    let full = SearchOptions {
+        null_move_pruning: false,
+        late_move_reductions: false,
+        reverse_futility_pruning: false,
+        futility_pruning: false,
+        late_move_pruning: false,
+        ..deterministic()
+    };
+
+    for options in [&pruned, &full] {
+        let result = go(MATE_IN_TWO, &["depth", "4"], options).await;
+        assert_eq!(result.best_move.to_string(), "d5f6");
+        assert_eq!(result.score, search::MATE - 3);
+    }
+    let pruned_nodes = go(KIWIPETE, &["depth", "5"], &pruned).await.nodes;
+    let full_nodes = go(KIWIPETE, &["depth", "5"], &full).await.nodes;
+    assert!(pruned_nodes < full_nodes, "{pruned_nodes} {full_nodes}");
+}
+
+#[tokio::test]
+async fn check_extension_finds_a_deeper_mate() {
+    // Mate in four after Qxg7+, which a five ply search only sees with the checks extended
+    let fen = "r2r1n2/pp2bk2/2p1p2p/3q4/3PN1QP/2P3R1/P4PP1/5RK1 w - - 0 1";
+    let extended = go(fen, &["depth", "5"], &deterministic()).await;
+    let unextended = go(fen, &["depth", "5"], &SearchOptions { check_extension: false, ..deterministic() }).await;
+
+    assert_eq!(extended.best_move.to_string(), "g4g7");
+    assert_eq!(extended.score, search::MATE - 7);
+    assert!(unextended.score < search::MATE - 100, "{}", unextended.score);
+}
+
+#[tokio::test]
+async fn lazy_smp_finds_the_mate() {
+    // The threads share the transposition table and search at different depths, the result is still a mate
+    let options = SearchOptions { threads: 3, ..SearchOptions::default() };
+    let result = go(MATE_IN_TWO, &["depth", "5"], &options).await;
+
+    assert_eq!(result.best_move.to_string(), "d5f6");
+    assert_eq!(result.score, search::MATE - 3);
+
+    let result = go(KIWIPETE, &["depth", "5"], &options).await;
+    assert!(load(KIWIPETE).generate_legal_moves().contains(&result.best_move));
+}
@@ -0,0 +1,143 @@
+// Texel tuning on a small dataset: the scale K, the gradient, the optimizer and the written tables
+use chess_v3::evaluation::piece_square_value;
+use chess_v3::tuner::{self, Entry};
+use chess_v3::types::*;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+// Material imbalances won by the side ahead, a few of them drawn all the same
+const DATASET: &str = "\
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 [0.5]
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1 [0.0]
+rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1 [1.0]
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1 [0.0]
+1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b Qkq - 0 1 [1.0]
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/R1BQKBNR w KQkq - 0 1 [0.5]
+r1bqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1 [1.0]
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RN1QKBNR w KQkq - 0 1 [0.0]
+rn1qkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1 [0.5]
+rnbqkbnr/pppppppp/8/8/8/8/PPP1PPPP/RNBQKBNR w KQkq - 0 1 [0.5]
+rnbqkbnr/ppp1pppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1 [1.0]
+rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2 | 10 | 1/2-1/2
+r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3 | 25 | 1-0
+4k3/8/8/8/8/8/4P3/4K3 w - - 0 1 | 150 | 1-0
+4k3/4p3/8/8/8/8/8/4K3 b - - 0 1 | -150 | 0-1
+4k3/8/8/8/8/8/8/R3K3 w - - 0 1 | 900 | 1-0
+";
+
+fn load_dataset(name: &str) -> Vec<Entry> {
+    let path = std::env::temp_dir().join(format!("chess_v3_tuner_{}_{name}.txt", std::process::id()));
+    std::fs::write(&path, DATASET).unwrap();
+    let entries = tuner::load_dataset(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(entries.len(), DATASET.lines().count());
+    entries
+}
+
+// The tables of a file written by write_tables, by name
+fn read_tables(path: &PathBuf) -> HashMap<String, Vec<i32>> {
+    let text = std::fs::read_to_string(path).unwrap();
+    let mut tables = HashMap::new();
+    for block in text.split("pub const ").skip(1) {
+        let (name, values) = block.split_once(": [i32; 64] = [").unwrap();
+        let values = values.split(']').next().unwrap();
+        tables.insert(name.to_string(), values.split(',').filter_map(|value| value.trim().parse().ok()).collect());
+    }
+    tables
+}
+
+fn write_tables(name: &str, parameters: &[f64]) -> HashMap<String, Vec<i32>> {
+    let path = std::env::temp_dir().join(format!("chess_v3_tuner_{}_{name}.rs", std::process::id()));
+    tuner::write_tables(parameters, path.to_str().unwrap()).unwrap();
+    let tables = read_tables(&path);
+    std::fs::remove_file(&path).unwrap();
+    tables
+}
+
+fn mean(values: &[i32]) -> f64 {
+    values.iter().sum::<i32>() as f64 / values.len() as f64
+}
+
+#[test]
+fn k_minimizes_the_error() {
+    let entries = load_dataset("k");
+    let parameters = tuner::initial_parameters();
+    let k = tuner::find_k(&entries, &parameters);
+    let error = tuner::mean_squared_error(&entries, &parameters, k);
+
+    assert!(k > 0.0);
+    assert!(error <= tuner::mean_squared_error(&entries, &parameters, k - 0.01));
+    assert!(error <= tuner::mean_squared_error(&entries, &parameters, k + 0.01));
+}
+
+#[test]
+fn gradient_matches_finite_differences() {
+    let entries = load_dataset("gradient");
+    let mut parameters = tuner::initial_parameters();
+    let k = tuner::find_k(&entries, &parameters);
+    let gradient = tuner::gradient(&entries, &parameters, k);
+
+    // The parameters with the steepest slopes, material, squares and term weights among them
+    let mut indices = (0..gradient.len()).collect::<Vec<usize>>();
+    indices.sort_by(|a, b| gradient[*b].abs().total_cmp(&gradient[*a].abs()));
+    for index in indices.into_iter().take(20) {
+        let step = 0.01;
+        let value = parameters[index];
+        parameters[index] = value + step;
+        let above = tuner::mean_squared_error(&entries, &parameters, k);
+        parameters[index] = value - step;
+        let below = tuner::mean_squared_error(&entries, &parameters, k);
+        parameters[index] = value;
+
+        let slope = (above - below) / (2.0 * step);
+        assert!((slope - gradient[index]).abs() <= 1e-3 * gradient[index].abs(), "{index}: {slope} {}", gradient[index]);
+    }
+}
+
+#[test]
+fn tuning_lowers_the_error() {
+    let entries = load_dataset("optimize");
+    let mut parameters = tuner::initial_parameters();
+    let k = tuner::find_k(&entries, &parameters);
+    let initial_error = tuner::mean_squared_error(&entries, &parameters, k);
+    let initial_tables = write_tables("initial", &parameters);
+
+    tuner::optimize(&entries, &mut parameters, k, 50);
+    assert!(tuner::mean_squared_error(&entries, &parameters, k) < initial_error);
+
+    // Material takes up any change of level, the tables only change shape
+    let tables = write_tables("optimized", &parameters);
+    for (name, values) in &tables {
+        let squares = if name.ends_with("PAWN") { 8..56 } else { 0..64 };
+        let (before, after) = (mean(&initial_tables[name][squares.clone()]), mean(&values[squares]));
+        assert!((before - after).abs() <= 0.5, "{name}: {before} {after}");
+    }
+    assert_ne!(tables, initial_tables);
+}
+
+#[test]
+fn written_tables_are_the_evaluation_tables() {
+    let tables = write_tables("written", &tuner::initial_parameters());
+    assert_eq!(tables.len(), 14);
+
+    let names = [
+        (PieceType::Pawn, "PAWN"),
+        (PieceType::Rook, "ROOK"),
+        (PieceType::Knight, "KNIGHT"),
+        (PieceType::Bishop, "BISHOP"),
+        (PieceType::Queen, "QUEEN"),
+        (PieceType::King, "KING"),
+    ];
+    for (typ, name) in names {
+        for (color, side) in [(Color::White, "WHITE"), (Color::Black, "BLACK")] {
+            for square in Square::ALL {
+                let value = piece_square_value(&Piece { typ, color }, square);
+                assert_eq!(tables[&format!("{side}_{name}")][square as usize], value.mg, "{side}_{name} {square}");
+                if typ == PieceType::King {
+                    assert_eq!(tables[&format!("{side}_KING_END_GAME")][square as usize], value.eg, "{side} {square}");
+                }
+            }
+        }
+    }
+}
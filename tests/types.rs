@@ -0,0 +1,80 @@
+// Checks the square, bitboard and move types against the moves of a few positions
+use chess_v3::board::Board;
+use chess_v3::consts::EMPTY_BOARD;
+use chess_v3::types::*;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn load(fen: &str) -> Board {
+    let mut board = EMPTY_BOARD;
+    board.load_fen(fen.to_string());
+    board
+}
+
+#[test]
+fn square_names() {
+    for (index, square) in Square::ALL.into_iter().enumerate() {
+        assert_eq!(square as usize, index);
+        assert_eq!(square.to_string().parse::<Square>(), Ok(square));
+        assert_eq!(Square::from_file_rank(square.file(), square.rank()), square);
+    }
+    assert_eq!(Square::E4.to_string(), "e4");
+    assert_eq!(Square::A1.flip(), Square::A8);
+    assert!("i1".parse::<Square>().is_err());
+    assert_eq!(Rank::R2.relative(Color::Black), Rank::R7);
+}
+
+#[test]
+fn bitboard_iteration() {
+    let bitboard = Square::A1.bitboard() | Square::E4.bitboard() | Square::H8.bitboard();
+    assert_eq!(bitboard.count(), 3);
+    assert_eq!(bitboard.collect::<Vec<Square>>(), [Square::A1, Square::E4, Square::H8]);
+    assert_eq!((bitboard.lsb(), bitboard.msb()), (Square::A1, Square::H8));
+    assert!(bitboard.contains(Square::E4) && !bitboard.contains(Square::E5));
+    assert_eq!(File::E.bitboard() & Rank::R4.bitboard(), Square::E4.bitboard());
+    assert_eq!(Square::E4.bitboard() << 8, Square::E5.bitboard());
+    assert_eq!(Bitboard::EMPTY.count(), 0);
+}
+
+#[test]
+fn move_flags() {
+    let mut board = load(KIWIPETE);
+    let moves = board.generate_legal_moves();
+    let find = |name: &str| *moves.iter().find(|mov| mov.to_string() == name).unwrap();
+
+    assert!(find("e1g1").is_castle() && find("e1c1").is_castle());
+    assert!(find("e5f7").is_capture() && !find("e5f7").is_castle());
+    assert!(!find("a2a4").is_capture());
+    for mov in &moves {
+        assert_eq!(Move::from_bits(mov.bits()), *mov);
+        assert_eq!(board.string_to_move(&mov.to_string()), Some(*mov));
+    }
+
+    board.make_move(find("a2a4"));
+    assert_eq!(board.en_passant, Some(Square::A3));
+    let en_passant = board.string_to_move("b4a3").unwrap();
+    assert!(en_passant.is_en_passant() && en_passant.is_capture());
+
+    let board = load("8/1P6/8/8/8/8/8/k1K5 w - - 0 1");
+    let promotion = board.string_to_move("b7b8n").unwrap();
+    assert_eq!(promotion.promotion(), PieceType::Knight);
+    assert!(promotion.is_promotion() && !promotion.is_capture());
+}
+
+#[test]
+fn move_list_capacity() {
+    // The position with the most legal moves known
+    let mut board = load("R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - - 0 1");
+    assert_eq!(board.generate_legal_moves().len(), 218);
+    assert!(board.generate_moves().len() < MoveList::CAPACITY);
+}
+
+#[test]
+#[should_panic(expected = "More than 256 moves generated in one position")]
+fn move_list_overflow_panics() {
+    let mut list = MoveList::new();
+    let mov = load(KIWIPETE).generate_moves().into_iter().next().unwrap();
+    for _ in 0..=MoveList::CAPACITY {
+        list.push(mov);
+    }
+}
@@ -0,0 +1,121 @@
+// Runs the engine binary on UCI input and checks what it answers
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+// Feeds the commands to one engine process and returns everything it printed before exiting
+fn run_engine(input: &str) -> String {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_chess_v3"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Could not start the engine");
+
+    // Dropping stdin at the end of the block closes the input
+    engine.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    String::from_utf8(engine.wait_with_output().unwrap().stdout).unwrap()
+}
+
+// Feeds the commands and keeps the input open until the search ends by itself and prints its bestmove
+fn run_search(input: &str) -> String {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_chess_v3"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Could not start the engine");
+
+    let mut stdin = engine.stdin.take().unwrap();
+    stdin.write_all(input.as_bytes()).unwrap();
+    stdin.flush().unwrap();
+
+    let mut output = String::new();
+    for line in BufReader::new(engine.stdout.take().unwrap()).lines() {
+        let line = line.unwrap();
+        output += &line;
+        output += "\n";
+        if line.starts_with("bestmove ") {
+            break;
+        }
+    }
+    stdin.write_all(b"quit\n").unwrap();
+    drop(stdin);
+    engine.wait().unwrap();
+    output
+}
+
+fn bestmoves(output: &str) -> usize {
+    output.lines().filter(|line| line.starts_with("bestmove ")).count()
+}
+
+#[test]
+fn quit_answers_a_running_search() {
+    assert_eq!(bestmoves(&run_engine("go infinite\nquit\n")), 1);
+}
+
+#[test]
+fn end_of_input_answers_a_running_search() {
+    assert_eq!(bestmoves(&run_engine("go infinite\n")), 1);
+    assert_eq!(bestmoves(&run_engine("position startpos moves e2e4\ngo ponder wtime 1000 btime 1000\n")), 1);
+}
+
+#[test]
+fn stop_answers_once() {
+    assert_eq!(bestmoves(&run_engine("go infinite\nstop\nquit\n")), 1);
+    assert_eq!(bestmoves(&run_engine("go depth 2\nisready\nquit\n")), 1);
+}
+
+// The score of each MultiPV line of every completed depth, in the order they were reported
+fn multi_pv_scores(output: &str) -> Vec<(usize, Vec<i32>)> {
+    let mut depths: Vec<(usize, Vec<i32>)> = Vec::new();
+    for line in output.lines().filter(|line| line.starts_with("info depth") && !line.contains("bound")) {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        let value = |name: &str| words.iter().position(|word| *word == name).map(|index| words[index + 1]);
+        let (Some(depth), Some(score)) = (value("depth"), value("cp")) else {
+            continue;
+        };
+        let depth = depth.parse().unwrap();
+        if depths.last().is_none_or(|(last, _)| *last != depth) {
+            depths.push((depth, Vec::new()));
+        }
+        depths.last_mut().unwrap().1.push(score.parse().unwrap());
+    }
+    depths
+}
+
+#[test]
+fn multi_pv_lines_in_score_order() {
+    // Positions where a later line used to come back with a better score than an earlier one
+    for fen in [
+        "3r1rk1/p5pp/bpp1pp2/8/q1PP1P2/b3P3/P2NQRPP/1R2B1K1 b - - 6 22",
+        "6k1/6p1/6Pp/ppp5/3pn2P/1P3K2/1PP2P2/3N4 b - - 0 1",
+        "8/8/1P6/5pr1/8/4R3/7k/2K5 w - - 0 1",
+    ] {
+        let output = run_search(&format!("setoption name MultiPV value 4\nposition fen {fen}\ngo depth 4\n"));
+        let depths = multi_pv_scores(&output);
+        assert_eq!(depths.len(), 4, "{output}");
+        for (depth, scores) in depths {
+            assert!(scores.is_sorted_by(|a, b| a >= b), "{fen} at depth {depth}: {scores:?}");
+        }
+
+        // The best move is the first line's
+        let last_first_line = output.lines().rfind(|line| line.contains(" multipv 1 ") && !line.contains("bound")).unwrap();
+        let first_move = last_first_line.split(" pv ").nth(1).unwrap().split_whitespace().next().unwrap();
+        let bestmove = output.lines().find_map(|line| line.strip_prefix("bestmove ")).unwrap();
+        assert_eq!(bestmove.split_whitespace().next(), Some(first_move), "{output}");
+    }
+}
+
+#[test]
+fn aspiration_windows_end_with_an_exact_score() {
+    // The score climbs past the window several times at depth 5
+    let fen = "6k1/3b3r/1p1p4/p1n2p2/1PPNpP1q/P3Q1p1/1R1RB1P1/5K2 b - - 0 1";
+    let output = run_search(&format!("position fen {fen}\ngo depth 6\n"));
+    let lines = output.lines().filter(|line| line.starts_with("info depth")).collect::<Vec<&str>>();
+    assert!(lines.iter().any(|line| line.contains(" lowerbound ")), "{output}");
+
+    // Every failed window is searched again until the score of the depth is exact
+    for depth in 1..=6 {
+        let prefix = format!("info depth {depth} ");
+        let last = lines.iter().rfind(|line| line.starts_with(&prefix)).expect(&output);
+        assert!(!last.contains("bound"), "{output}");
+    }
+}